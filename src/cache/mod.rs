@@ -3,7 +3,11 @@
 //! Provides caching of command results and tool availability checks
 //! to improve performance on repeated runs.
 
+pub mod results;
+pub mod tools;
+
 use std::collections::HashMap;
+use tools::{ToolCache, ToolInfo, ToolSpec};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -75,20 +79,35 @@ impl<T: Clone> Cache<T> {
 
 // Global cache for tool availability checks
 lazy_static::lazy_static! {
-    pub static ref TOOL_CACHE: Cache<bool> = Cache::new(300); // 5 minutes TTL
+    pub static ref TOOL_CACHE: Cache<ToolInfo> = Cache::new(300); // 5 minutes TTL
+    static ref STORED_TOOLS: ToolCache = ToolCache::new(ToolCache::default_path());
 }
 
-/// Cached check for tool availability
-pub fn has_tool_cached(tool_name: &str, check_fn: impl FnOnce() -> bool) -> bool {
-    let cache_key = format!("tool_{}", tool_name);
+/// Cached detection of a tool and its version
+///
+/// Answers are kept for the rest of the process and stored on disk for
+/// later runs.
+pub fn detect_tool(spec: &ToolSpec) -> ToolInfo {
+    let cache_key = format!("tool_{}", spec.name);
 
     if let Some(cached) = TOOL_CACHE.get(&cache_key) {
         return cached;
     }
 
-    let available = check_fn();
-    TOOL_CACHE.insert(cache_key, available);
-    available
+    let info = STORED_TOOLS.detect(spec);
+    TOOL_CACHE.insert(cache_key, info.clone());
+    info
+}
+
+/// Detection of a tool that leaves no trace
+///
+/// Answers known from earlier detections are reused, but new ones are
+/// neither kept nor stored.
+pub fn probe_tool(spec: &ToolSpec) -> ToolInfo {
+    match TOOL_CACHE.get(&format!("tool_{}", spec.name)) {
+        Some(cached) => cached,
+        None => STORED_TOOLS.probe(spec),
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,399 @@
+//! Persistent cache of check results
+//!
+//! Each check's latest result is stored under `target/cargo-status/results/`
+//! together with a fingerprint of everything that can change it: the
+//! workspace sources, `Cargo.lock`, the toolchain, the command line and the
+//! environment. While the fingerprint matches, the stored result is replayed
+//! instead of running the command again.
+
+use crate::display::CheckStatus;
+use crate::tools::{CheckResult, Diagnostic, TestCase, TestCounts};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+/// Checks whose results depend on more than the workspace
+///
+/// `cargo audit` reads the advisory database, which changes independently.
+const UNCACHED_CHECKS: [&str; 1] = ["Audit"];
+
+/// Prefixes of environment variables that change what cargo and rustc do
+const FINGERPRINT_ENV_PREFIXES: [&str; 6] = [
+    "RUST",
+    "CARGO_BUILD_",
+    "CARGO_ENCODED_",
+    "CARGO_INCREMENTAL",
+    "CARGO_PROFILE_",
+    "CARGO_TARGET_",
+];
+
+/// Stored results of checks, valid while their inputs are unchanged
+#[derive(Debug, Clone)]
+pub struct ResultCache {
+    dir: PathBuf,
+    /// Root of the workspace and the files a run writes into it
+    root: PathBuf,
+    outputs: Vec<PathBuf>,
+    /// Fingerprint of the inputs shared by all checks
+    inputs: String,
+    /// Run every check again, but still store the new results
+    refresh: bool,
+}
+
+/// A check result as stored on disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedResult {
+    pub status: CachedStatus,
+    pub exit_code: Option<i32>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub tests: Option<TestCounts>,
+    pub test_cases: Vec<TestCase>,
+    pub output: Vec<String>,
+}
+
+/// Statuses worth replaying; failures of the check itself are always retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CachedStatus {
+    Success {
+        warnings: usize,
+        duration: Duration,
+    },
+    Warning {
+        warnings: usize,
+        duration: Duration,
+    },
+    Error {
+        errors: usize,
+        warnings: usize,
+        duration: Duration,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    key: String,
+    result: CachedResult,
+}
+
+impl ResultCache {
+    /// Opens the cache in `dir` for the workspace at `root`
+    ///
+    /// Reads every source file of the workspace to fingerprint it, leaving
+    /// out `outputs`, the reports and badges a run writes.
+    pub fn new(dir: PathBuf, root: &Path, outputs: Vec<PathBuf>) -> io::Result<Self> {
+        let inputs = fingerprint_inputs(root, &outputs)?;
+        Ok(Self {
+            dir,
+            root: root.to_path_buf(),
+            outputs,
+            inputs,
+            refresh: false,
+        })
+    }
+
+    /// Runs every check again instead of replaying stored results
+    pub fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Cache key of a check, or `None` if its results can't be cached
+    pub fn key(&self, name: &str, command: &[String], env: &[(String, String)]) -> Option<String> {
+        if UNCACHED_CHECKS.contains(&name) {
+            return None;
+        }
+
+        let mut hasher = Sha256::new();
+        update_field(&mut hasher, env!("CARGO_PKG_VERSION").as_bytes());
+        update_field(&mut hasher, self.inputs.as_bytes());
+        update_field(&mut hasher, name.as_bytes());
+        for arg in command {
+            update_field(&mut hasher, arg.as_bytes());
+        }
+        for (key, value) in env {
+            update_field(&mut hasher, format!("{}={}", key, value).as_bytes());
+        }
+
+        let mut process_env: Vec<(String, String)> = std::env::vars()
+            .filter(|(key, _)| FINGERPRINT_ENV_PREFIXES.iter().any(|prefix| key.starts_with(prefix)))
+            .collect();
+        process_env.sort();
+        for (key, value) in process_env {
+            update_field(&mut hasher, format!("{}={}", key, value).as_bytes());
+        }
+        Some(hex(&hasher.finalize()))
+    }
+
+    /// Stored result of a check, if it was stored under the same key
+    pub fn load(&self, name: &str, key: &str) -> Option<CachedResult> {
+        if self.refresh {
+            return None;
+        }
+        let text = fs::read_to_string(self.entry_path(name)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&text).ok()?;
+        (entry.key == key).then_some(entry.result)
+    }
+
+    /// Stores the result of a check that ran to completion
+    ///
+    /// Nothing is stored if the sources changed while the check ran, as the
+    /// result may belong to neither version of them.
+    pub fn store(&self, name: &str, key: &str, result: &CheckResult) -> io::Result<()> {
+        let status = match result.status {
+            CheckStatus::Success { warnings, duration } => CachedStatus::Success { warnings, duration },
+            CheckStatus::Warning { warnings, duration } => CachedStatus::Warning { warnings, duration },
+            CheckStatus::Error {
+                errors,
+                warnings,
+                duration,
+            } => CachedStatus::Error {
+                errors,
+                warnings,
+                duration,
+            },
+            _ => return Ok(()),
+        };
+        if fingerprint_inputs(&self.root, &self.outputs)? != self.inputs {
+            return Ok(());
+        }
+        let entry = CacheEntry {
+            key: key.to_string(),
+            result: CachedResult {
+                status,
+                exit_code: result.exit_code,
+                diagnostics: result.diagnostics.clone(),
+                tests: result.tests,
+                test_cases: result.test_cases.clone(),
+                output: result.output.clone(),
+            },
+        };
+
+        fs::create_dir_all(&self.dir)?;
+        let path = self.entry_path(name);
+        // Write then rename so a concurrent run never reads a partial entry
+        let partial = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&partial, serde_json::to_vec(&entry)?)?;
+        fs::rename(&partial, &path)
+    }
+
+    fn entry_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name.to_lowercase()))
+    }
+}
+
+impl CachedStatus {
+    /// The status shown when the result is replayed
+    pub fn to_status(self) -> CheckStatus {
+        match self {
+            CachedStatus::Success { warnings, duration } => CheckStatus::Success { warnings, duration },
+            CachedStatus::Warning { warnings, duration } => CheckStatus::Warning { warnings, duration },
+            CachedStatus::Error {
+                errors,
+                warnings,
+                duration,
+            } => CheckStatus::Error {
+                errors,
+                warnings,
+                duration,
+            },
+        }
+    }
+}
+
+/// Fingerprint of the sources, `Cargo.lock` and toolchain of a workspace
+fn fingerprint_inputs(root: &Path, outputs: &[PathBuf]) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+
+    let mut files = source_files(root, outputs);
+    // Libraries often leave Cargo.lock out of version control
+    if !files.iter().any(|file| file == Path::new("Cargo.lock")) && root.join("Cargo.lock").is_file() {
+        files.push(PathBuf::from("Cargo.lock"));
+    }
+    files.sort();
+    for file in files {
+        let Ok(contents) = fs::read(root.join(&file)) else {
+            continue;
+        };
+        update_field(&mut hasher, file.to_string_lossy().as_bytes());
+        update_field(&mut hasher, &contents);
+    }
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let toolchain = Command::new(rustc).arg("-vV").current_dir(root).output()?;
+    update_field(&mut hasher, &toolchain.stdout);
+
+    Ok(hex(&hasher.finalize()))
+}
+
+/// Files of the workspace at `root`, relative to it
+///
+/// Uses git's view of the tree when available, so ignored files don't count.
+/// Otherwise walks the directory, skipping hidden directories. Build output
+/// in `target` (or `CARGO_TARGET_DIR`) is left out either way, even when it
+/// isn't ignored, and so are `outputs`: files cargo-status itself writes,
+/// absolute or relative to `root`.
+pub fn source_files(root: &Path, outputs: &[PathBuf]) -> Vec<PathBuf> {
+    let build_dirs = build_dirs(root);
+    let outputs: Vec<&Path> = outputs
+        .iter()
+        .filter_map(|path| match path.strip_prefix(root) {
+            Ok(relative) => Some(relative),
+            Err(_) if path.is_relative() => Some(path.as_path()),
+            Err(_) => None,
+        })
+        .collect();
+    let is_source = |path: &PathBuf| {
+        !build_dirs.iter().any(|dir| path.starts_with(dir)) && !outputs.contains(&path.as_path())
+    };
+    let listed = Command::new("git")
+        .args(["ls-files", "-z", "--cached", "--others", "--exclude-standard"])
+        .current_dir(root)
+        .output();
+    if let Ok(output) = listed
+        && output.status.success()
+    {
+        return output
+            .stdout
+            .split(|byte| *byte == 0)
+            .filter(|path| !path.is_empty())
+            .map(|path| PathBuf::from(String::from_utf8_lossy(path).into_owned()))
+            .filter(is_source)
+            .filter(|path| root.join(path).is_file())
+            .collect();
+    }
+
+    let mut files = Vec::new();
+    walk(root, Path::new(""), &mut files);
+    files.retain(is_source);
+    files
+}
+
+/// Build output directories inside the workspace, relative to `root`
+fn build_dirs(root: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from("target")];
+    if let Some(target_dir) = std::env::var_os("CARGO_TARGET_DIR").map(PathBuf::from) {
+        if target_dir.is_relative() {
+            dirs.push(target_dir);
+        } else if let Ok(relative) = target_dir.strip_prefix(root) {
+            dirs.push(relative.to_path_buf());
+        }
+    }
+    dirs
+}
+
+fn walk(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(root.join(relative)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let path = relative.join(&name);
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            let name = name.to_string_lossy();
+            if !name.starts_with('.') && name != "target" {
+                walk(root, &path, files);
+            }
+        } else if file_type.is_file() {
+            files.push(path);
+        }
+    }
+}
+
+/// Hashes a length-prefixed field, so adjacent fields can't run together
+pub(crate) fn update_field(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspace() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "pub fn demo() {}\n").unwrap();
+        fs::write(dir.path().join("target/debug/demo"), "binary").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_source_fingerprint() {
+        let dir = workspace();
+        let before = fingerprint_inputs(dir.path(), &[]).unwrap();
+
+        // Rewriting a file with the same contents or touching the target
+        // directory leaves the fingerprint alone
+        fs::write(dir.path().join("src/lib.rs"), "pub fn demo() {}\n").unwrap();
+        fs::write(dir.path().join("target/debug/demo"), "rebuilt").unwrap();
+        assert_eq!(fingerprint_inputs(dir.path(), &[]).unwrap(), before);
+
+        // Nor do the reports a run writes
+        let outputs = vec![dir.path().join("report.json"), PathBuf::from("status.svg")];
+        let before = fingerprint_inputs(dir.path(), &outputs).unwrap();
+        fs::write(dir.path().join("report.json"), "{\"started_at\": 1}").unwrap();
+        fs::write(dir.path().join("status.svg"), "<svg/>").unwrap();
+        assert_eq!(fingerprint_inputs(dir.path(), &outputs).unwrap(), before);
+
+        fs::write(dir.path().join("src/lib.rs"), "pub fn demo() { todo!() }\n").unwrap();
+        assert_ne!(fingerprint_inputs(dir.path(), &outputs).unwrap(), before);
+    }
+
+    #[test]
+    fn test_store_and_load() {
+        let dir = workspace();
+        let cache = ResultCache::new(dir.path().join("target/cargo-status/results"), dir.path(), Vec::new()).unwrap();
+        let command = vec!["cargo".to_string(), "check".to_string()];
+        let key = cache.key("Check", &command, &[]).unwrap();
+        assert_ne!(cache.key("Check", &command, &[("A".to_string(), "1".to_string())]), Some(key.clone()));
+        assert_eq!(cache.key("Audit", &command, &[]), None);
+
+        let duration = Duration::from_millis(800);
+        let result = CheckResult {
+            exit_code: Some(0),
+            output: vec!["warning: unused".to_string()],
+            ..CheckResult::for_test("Check", CheckStatus::Warning { warnings: 1, duration })
+                .with_diagnostics(vec![Diagnostic::new(crate::tools::DiagnosticLevel::Warning, "unused")])
+        };
+        cache.store("Check", &key, &result).unwrap();
+
+        let cached = cache.load("Check", &key).unwrap();
+        assert_eq!(cached.status.to_status(), result.status);
+        assert_eq!(cached.diagnostics, result.diagnostics);
+        assert_eq!(cached.output, result.output);
+        assert!(cache.load("Check", "other").is_none());
+        assert!(cache.clone().with_refresh(true).load("Check", &key).is_none());
+
+        // Failures of the check itself are not stored
+        let failed = CheckResult {
+            status: CheckStatus::Failed {
+                reason: "spawn failed".to_string(),
+                duration,
+            },
+            ..result.clone()
+        };
+        cache.store("Build", &key, &failed).unwrap();
+        assert!(cache.load("Build", &key).is_none());
+
+        // A result for sources edited while the check ran is not stored
+        fs::write(dir.path().join("src/lib.rs"), "pub fn demo() { todo!() }\n").unwrap();
+        cache.store("Clippy", &key, &result).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "pub fn demo() {}\n").unwrap();
+        assert!(cache.load("Clippy", &key).is_none());
+    }
+}
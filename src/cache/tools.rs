@@ -0,0 +1,333 @@
+//! Tool detection that persists across runs
+//!
+//! Finding out whether clippy, cargo-audit or cargo-nextest is installed means
+//! spawning `cargo <tool> --version`. The answer and the reported version are
+//! stored in `tools.json` in the user's cache directory, keyed by the paths
+//! and modification times of the cargo, rustup and `cargo-<tool>` binaries and
+//! by the active toolchain, so installing, updating or switching tools is
+//! noticed on the next run.
+
+use crate::cache::results::{hex, update_field};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::UNIX_EPOCH;
+
+/// Whether a tool is installed, and which version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolInfo {
+    pub available: bool,
+    /// First line of `--version`, e.g. `clippy 0.1.83 (90b35a6 2024-11-26)`
+    pub version: Option<String>,
+}
+
+/// Numeric version of a tool, for gating version-specific flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ToolVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+/// How a tool is detected
+#[derive(Debug, Clone, Copy)]
+pub struct ToolSpec {
+    pub name: &'static str,
+    /// Command printing the version, e.g. `cargo clippy --version`
+    pub command: &'static [&'static str],
+    /// Binary on `PATH` whose installation or update changes the answer
+    pub binary: Option<&'static str>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ToolCacheFile {
+    tools: BTreeMap<String, CachedTool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedTool {
+    key: String,
+    #[serde(flatten)]
+    info: ToolInfo,
+}
+
+/// Tool detection results stored in a file
+#[derive(Debug, Clone)]
+pub struct ToolCache {
+    path: Option<PathBuf>,
+    /// Fingerprint of the cargo and rustup installation and active toolchain
+    environment: String,
+}
+
+impl ToolCache {
+    /// Opens the cache stored at `path`; without a path nothing is stored
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            environment: environment_key(),
+        }
+    }
+
+    /// `tools.json` in the user's cache directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("cargo-status").join("tools.json"))
+    }
+
+    /// Detects a tool, reusing the stored answer while its key is unchanged
+    pub fn detect(&self, spec: &ToolSpec) -> ToolInfo {
+        let key = self.key(spec);
+        let mut file = self.load();
+        if let Some(cached) = file.tools.get(spec.name)
+            && cached.key == key
+        {
+            return cached.info.clone();
+        }
+
+        let info = run_version_command(spec.command);
+        file.tools.insert(
+            spec.name.to_string(),
+            CachedTool {
+                key,
+                info: info.clone(),
+            },
+        );
+        // Detection still works when the cache can't be written
+        let _ = self.save(&file);
+        info
+    }
+
+    /// Like [`Self::detect`], but stores nothing
+    pub fn probe(&self, spec: &ToolSpec) -> ToolInfo {
+        let key = self.key(spec);
+        match self.load().tools.get(spec.name) {
+            Some(cached) if cached.key == key => cached.info.clone(),
+            _ => run_version_command(spec.command),
+        }
+    }
+
+    fn key(&self, spec: &ToolSpec) -> String {
+        let mut hasher = Sha256::new();
+        update_field(&mut hasher, self.environment.as_bytes());
+        update_field(&mut hasher, spec.command.join(" ").as_bytes());
+        if let Some(binary) = spec.binary {
+            update_field(&mut hasher, binary_stamp(find_in_path(binary).as_deref()).as_bytes());
+        }
+        hex(&hasher.finalize())
+    }
+
+    fn load(&self) -> ToolCacheFile {
+        self.path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, file: &ToolCacheFile) -> std::io::Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let partial = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&partial, serde_json::to_vec_pretty(file)?)?;
+        fs::rename(&partial, path)
+    }
+}
+
+impl ToolInfo {
+    /// Version number parsed from the version string
+    pub fn version_number(&self) -> Option<ToolVersion> {
+        self.version.as_deref().and_then(ToolVersion::parse)
+    }
+}
+
+impl ToolVersion {
+    /// Finds the first `MAJOR.MINOR[.PATCH]` in a version string
+    pub fn parse(text: &str) -> Option<Self> {
+        text.split_whitespace().find_map(|word| {
+            let word = word.trim_start_matches('v');
+            let core = word.split(['-', '+']).next()?;
+            let mut parts = core.split('.').map(|part| part.parse::<u64>());
+            let major = parts.next()?.ok()?;
+            let minor = parts.next()?.ok()?;
+            let patch = match parts.next() {
+                Some(patch) => patch.ok()?,
+                None => 0,
+            };
+            Some(Self {
+                major,
+                minor,
+                patch,
+            })
+        })
+    }
+
+    /// Whether this version is the given one or newer
+    pub fn at_least(self, major: u64, minor: u64, patch: u64) -> bool {
+        self >= Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl fmt::Display for ToolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Runs a `--version` command, recording its first line of output
+fn run_version_command(command: &[&str]) -> ToolInfo {
+    let output = Command::new(command[0])
+        .args(&command[1..])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => ToolInfo {
+            available: true,
+            version: String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty()),
+        },
+        _ => ToolInfo {
+            available: false,
+            version: None,
+        },
+    }
+}
+
+/// Fingerprint of the cargo and rustup installation and the active toolchain
+fn environment_key() -> String {
+    let mut hasher = Sha256::new();
+
+    // `cargo status` runs with $CARGO pointing at the toolchain's own cargo
+    let cargo = env::var_os("CARGO").map(PathBuf::from).or_else(|| find_in_path("cargo"));
+    update_field(&mut hasher, binary_stamp(cargo.as_deref()).as_bytes());
+    update_field(&mut hasher, binary_stamp(find_in_path("rustup").as_deref()).as_bytes());
+
+    for variable in ["RUSTUP_TOOLCHAIN", "RUSTUP_HOME", "CARGO_HOME", "PATH"] {
+        let value = env::var(variable).unwrap_or_default();
+        update_field(&mut hasher, format!("{}={}", variable, value).as_bytes());
+    }
+
+    // The default toolchain and overrides, and a stamp per toolchain update
+    if let Some(rustup_home) = rustup_home() {
+        update_field(&mut hasher, binary_stamp(Some(&rustup_home.join("settings.toml"))).as_bytes());
+        let mut updates: Vec<PathBuf> = fs::read_dir(rustup_home.join("update-hashes"))
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default();
+        updates.sort();
+        for update in updates {
+            update_field(&mut hasher, binary_stamp(Some(&update)).as_bytes());
+        }
+    }
+
+    // Toolchain files pin the toolchain for a directory tree
+    if let Ok(current_dir) = env::current_dir() {
+        for dir in current_dir.ancestors() {
+            for name in ["rust-toolchain", "rust-toolchain.toml"] {
+                if let Ok(contents) = fs::read(dir.join(name)) {
+                    update_field(&mut hasher, dir.join(name).to_string_lossy().as_bytes());
+                    update_field(&mut hasher, &contents);
+                }
+            }
+        }
+    }
+
+    hex(&hasher.finalize())
+}
+
+fn rustup_home() -> Option<PathBuf> {
+    env::var_os("RUSTUP_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".rustup")))
+}
+
+/// Finds an executable on `PATH`
+fn find_in_path(binary: &str) -> Option<PathBuf> {
+    let names = [binary.to_string(), format!("{}.exe", binary)];
+    env::split_paths(&env::var_os("PATH")?).find_map(|dir| {
+        names
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// Path and modification time of a file, or a marker that it is missing
+fn binary_stamp(path: Option<&Path>) -> String {
+    let Some(path) = path else {
+        return "missing".to_string();
+    };
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|since| since.as_nanos())
+        .unwrap_or_default();
+    format!("{}@{}", path.display(), modified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tool_version() {
+        let clippy = ToolVersion::parse("clippy 0.1.83 (90b35a6 2024-11-26)").unwrap();
+        assert_eq!(clippy.to_string(), "0.1.83");
+        assert!(clippy.at_least(0, 1, 80));
+        assert!(!clippy.at_least(0, 2, 0));
+
+        let nextest = ToolVersion::parse("cargo-nextest 0.9.72-b.1 (abc 2024-07-15)").unwrap();
+        assert_eq!(nextest, ToolVersion { major: 0, minor: 9, patch: 72 });
+        assert_eq!(ToolVersion::parse("rustc 1.83"), Some(ToolVersion { major: 1, minor: 83, patch: 0 }));
+        assert_eq!(ToolVersion::parse("no version here"), None);
+    }
+
+    #[test]
+    fn test_detect_persists_results() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("tools.json");
+        let spec = ToolSpec {
+            name: "echo",
+            command: &["echo", "echo 1.2.3"],
+            binary: None,
+        };
+
+        // Probing leaves nothing behind
+        assert!(ToolCache::new(Some(path.clone())).probe(&spec).available);
+        assert!(!path.exists());
+
+        let info = ToolCache::new(Some(path.clone())).detect(&spec);
+        assert!(info.available);
+        assert_eq!(info.version.as_deref(), Some("echo 1.2.3"));
+
+        // A later run reads the answer back instead of running the command
+        let mut file: ToolCacheFile = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        file.tools.get_mut("echo").unwrap().info.version = Some("echo 9.9.9".to_string());
+        fs::write(&path, serde_json::to_string(&file).unwrap()).unwrap();
+        let info = ToolCache::new(Some(path)).detect(&spec);
+        assert_eq!(info.version_number(), Some(ToolVersion { major: 9, minor: 9, patch: 9 }));
+
+        let missing = ToolSpec {
+            name: "missing",
+            command: &["cargo-status-no-such-tool", "--version"],
+            binary: None,
+        };
+        assert!(!ToolCache::new(None).detect(&missing).available);
+    }
+}
@@ -1,6 +1,11 @@
 //! Command line interface definitions for cargo-status
 
-use clap::{Parser, Subcommand};
+use crate::config::toml_config::TargetDirStrategy;
+use crate::events::EventFormat;
+use crate::report::ReportFormat;
+use crate::report::ci::CiProvider;
+use std::path::{Path, PathBuf};
+use clap::{Args, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 
 /// cargo-status - A fast, configurable Rust project status checker
@@ -23,6 +28,11 @@ pub enum Commands {
 /// Run status checks on your Rust project
 #[derive(Parser, Debug, Clone, Serialize, Deserialize, Default)]
 pub struct StatusArgs {
+    /// Task to run instead of the checks
+    #[command(subcommand)]
+    #[serde(skip)]
+    pub action: Option<StatusCommand>,
+
     /// Run cargo fmt (use -fv for verbose)
     #[arg(short = 'f', long = "fmt")]
     pub fmt: bool,
@@ -59,6 +69,18 @@ pub struct StatusArgs {
     #[arg(long = "sequential")]
     pub sequential: bool,
 
+    /// Maximum number of checks to run at the same time
+    #[arg(short = 'j', long = "jobs", value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Refuse to start checks while more than N child processes are running (0 disables)
+    #[arg(long = "max-processes", value_name = "N")]
+    pub max_processes: Option<usize>,
+
+    /// How parallel checks share target directories
+    #[arg(long = "target-dir-strategy", value_name = "STRATEGY")]
+    pub target_dir_strategy: Option<TargetDirStrategy>,
+
     /// Global verbose mode (shows output from all tools)
     #[arg(short = 'v', long = "verbose", action = clap::ArgAction::Count)]
     pub verbose: u8,
@@ -75,6 +97,73 @@ pub struct StatusArgs {
     #[arg(long = "build-args", value_name = "ARGS")]
     pub build_args: Option<String>,
 
+    /// Kill checks that run longer than SECS (use TOOL=SECS for a single tool)
+    #[arg(long = "timeout", value_name = "[TOOL=]SECS")]
+    pub timeout: Vec<String>,
+
+    /// Write a machine-readable report of the run (to stdout unless --output is given)
+    #[arg(long = "format", value_name = "FORMAT")]
+    pub format: Option<ReportFormat>,
+
+    /// Write the report to FILE instead of stdout; the terminal display stays on
+    #[arg(long = "output", value_name = "FILE", requires = "format")]
+    pub output: Option<PathBuf>,
+
+    /// CI annotations to produce (detected from the environment by default)
+    #[arg(long = "ci", value_name = "PROVIDER")]
+    pub ci: Option<CiProvider>,
+
+    /// Stream every status event as it happens (to stdout unless --events-output is given)
+    #[arg(long = "events", value_name = "FORMAT")]
+    pub events: Option<EventFormat>,
+
+    /// Write the event stream to FILE instead of stdout
+    #[arg(long = "events-output", value_name = "FILE", requires = "events")]
+    pub events_output: Option<PathBuf>,
+
+    /// Run every check instead of replaying unchanged results from the cache
+    #[arg(long = "no-cache")]
+    pub no_cache: bool,
+
+    /// Re-run the checks whenever a file in the workspace changes
+    #[arg(long = "watch")]
+    pub watch: bool,
+
+    /// Only check packages changed since BASE (default HEAD) and their dependents
+    #[arg(
+        long = "changed",
+        value_name = "BASE",
+        num_args = 0..=1,
+        default_missing_value = "HEAD"
+    )]
+    pub changed: Option<String>,
+
+    /// Break results down by workspace package in a crate-by-check matrix
+    #[arg(long = "per-package")]
+    pub per_package: bool,
+
+    /// Path to the Cargo.toml of the package or workspace to check
+    #[arg(long = "manifest-path", value_name = "PATH", conflicts_with = "scan")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Run the checks in every workspace under DIR, by default the `[scan]`
+    /// directories of the user config
+    #[arg(
+        long = "scan",
+        value_name = "DIR",
+        num_args = 0..,
+        conflicts_with_all = ["watch", "format", "output", "events", "events_output", "badge"]
+    )]
+    pub scan: Option<Vec<PathBuf>>,
+
+    /// Number of workspaces `--scan` checks at the same time
+    #[arg(long = "scan-jobs", value_name = "N", requires = "scan")]
+    pub scan_jobs: Option<usize>,
+
+    /// Also write SVG status badges for the run, e.g. status.svg
+    #[arg(long = "badge", value_name = "FILE")]
+    pub badge: Option<PathBuf>,
+
     /// Save current configuration as a profile
     #[arg(long = "save-profile")]
     pub save_profile: bool,
@@ -96,8 +185,67 @@ pub struct StatusArgs {
     pub no_color: bool,
 }
 
+/// Tasks of `cargo status` other than running checks
+#[derive(Subcommand, Debug, Clone)]
+pub enum StatusCommand {
+    /// Write SVG status badges summarising the last run
+    Badge(BadgeArgs),
+    /// Show or validate the settings a run would use
+    Config(ConfigArgs),
+}
+
+/// Arguments of `cargo status config`
+#[derive(Args, Debug, Clone)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigAction {
+    /// Print every setting with its source and the command line of each check
+    Show,
+    /// Report unknown keys and invalid values in the configuration
+    Validate,
+}
+
+/// Arguments of `cargo status badge`
+#[derive(Args, Debug, Clone)]
+pub struct BadgeArgs {
+    /// Summary badge to write; test and per-check badges are written next to it
+    #[arg(short = 'o', long = "output", value_name = "FILE", default_value = "status.svg")]
+    pub output: PathBuf,
+
+    /// JSON report to summarise instead of the last run
+    #[arg(long = "report", value_name = "FILE")]
+    pub report: Option<PathBuf>,
+}
 
 impl StatusArgs {
+    /// Makes the paths given on the command line absolute, so they keep
+    /// pointing to the same files after changing directory
+    pub fn make_paths_absolute(&mut self, base: &Path) {
+        let (badge_output, badge_report) = match self.action {
+            Some(StatusCommand::Badge(ref mut badge)) => (Some(&mut badge.output), badge.report.as_mut()),
+            _ => (None, None),
+        };
+        for path in [
+            self.manifest_path.as_mut(),
+            self.output.as_mut(),
+            self.events_output.as_mut(),
+            self.badge.as_mut(),
+            badge_output,
+            badge_report,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if path.is_relative() {
+                *path = base.join(&*path);
+            }
+        }
+    }
+
     /// Check if any tool-specific flags are set
     pub fn has_tool_flags(&self) -> bool {
         self.fmt || self.check || self.clippy || self.test || self.build || self.doc || self.audit
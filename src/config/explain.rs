@@ -0,0 +1,283 @@
+//! Explaining the settings of a run
+//!
+//! `cargo status config show` prints every setting with its effective value
+//! and the layer it came from, followed by the exact command line of each
+//! check that would run. `cargo status config validate` reports mistakes in
+//! the configuration instead of running anything.
+
+use crate::config::cli::StatusArgs;
+use crate::config::layers::{ConfigLayers, ConfigSource};
+use crate::config::{Config, TOOL_NAMES};
+use crate::error::Result;
+use crate::tools::status_check::StatusCheck;
+use colored::*;
+
+/// A setting with its effective value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setting {
+    /// Dotted key, as in `timeouts.test`
+    pub key: String,
+    pub value: String,
+    pub source: ConfigSource,
+}
+
+/// Effective settings of a run
+///
+/// Every top-level setting and the `checks` table are always listed; the
+/// other per-tool tables only list the tools they change.
+pub fn settings(config: &Config) -> Vec<Setting> {
+    let layers = &config.layers;
+    let toml_config = config.toml_config.as_ref();
+    let mut settings = Vec::new();
+    let mut add = |key: String, value: String| {
+        let source = layers.source(&key);
+        settings.push(Setting { key, value, source });
+    };
+
+    let enabled = config.args.get_enabled_tools();
+    for tool in TOOL_NAMES {
+        let value = config.args.all || enabled.iter().any(|name| name == tool);
+        add(format!("checks.{}", tool), value.to_string());
+    }
+    add("sequential".to_string(), config.args.sequential.to_string());
+    add("verbose".to_string(), config.is_verbose().to_string());
+    add("max_parallel".to_string(), limit(Some(config.max_parallel()).filter(|jobs| *jobs != usize::MAX)));
+    let tokens = config.jobserver_tokens();
+    add("jobserver".to_string(), tokens.is_some().to_string());
+    if let Some(tokens) = tokens {
+        add("build_jobs".to_string(), tokens.to_string());
+    }
+    add("max_processes".to_string(), limit(config.max_processes()));
+    let strategy = toml::Value::try_from(config.target_dir_strategy())
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .unwrap_or_default();
+    add("target_dir".to_string(), strategy);
+    if let Some(ref profile) = config.args.profile {
+        add("profile".to_string(), profile.clone());
+    }
+
+    for tool in TOOL_NAMES {
+        if config.is_tool_verbose(tool) && !config.is_verbose() {
+            add(format!("verbose_tools.{}", tool), "true".to_string());
+        }
+    }
+    if let Some(toml_config) = toml_config {
+        for tool in TOOL_NAMES {
+            let args = toml_config.tool_args.get(tool);
+            if !args.is_empty() {
+                add(format!("tool_args.{}", tool), quote_args(args));
+            }
+        }
+    }
+    for tool in TOOL_NAMES {
+        if let Some(timeout) = config.tool_timeout(tool) {
+            add(format!("timeouts.{}", tool), format!("{}s", timeout.as_secs()));
+        }
+    }
+    if let Some(toml_config) = toml_config {
+        for tool in TOOL_NAMES {
+            let deps = toml_config.dependencies.get(tool);
+            if !deps.is_empty() {
+                add(format!("dependencies.{}", tool), deps.join(", "));
+            }
+        }
+    }
+    settings
+}
+
+fn limit(value: Option<usize>) -> String {
+    value.map_or_else(|| "unlimited".to_string(), |value| value.to_string())
+}
+
+/// Renders the settings as aligned `key  value  source` rows
+pub fn render_settings(settings: &[Setting]) -> String {
+    let key_width = settings.iter().map(|setting| setting.key.len()).max().unwrap_or(0);
+    let value_width = settings.iter().map(|setting| setting.value.chars().count()).max().unwrap_or(0);
+    let mut out = format!("{}\n", "Settings:".bold());
+    for setting in settings {
+        let source = setting.source.to_string();
+        let source = match setting.source {
+            ConfigSource::Default => source.dimmed(),
+            _ => source.cyan(),
+        };
+        out.push_str(&format!(
+            "  {:<key_width$}  {:<value_width$}  {}\n",
+            setting.key, setting.value, source
+        ));
+    }
+    out
+}
+
+/// Renders the files and variables the settings were read from
+pub fn render_layers(layers: &ConfigLayers) -> String {
+    let mut out = format!("{}\n", "Sources:".bold());
+    if layers.layers.is_empty() {
+        out.push_str(&format!("  {}\n", "built-in defaults only".dimmed()));
+    }
+    let width = layers
+        .layers
+        .iter()
+        .map(|layer| layer.source.to_string().len())
+        .max()
+        .unwrap_or(0);
+    for layer in &layers.layers {
+        out.push_str(&format!("  {:<width$}  {}\n", layer.source.to_string(), layer.origin));
+    }
+    out
+}
+
+/// The command a check runs, as it could be typed into a shell
+pub fn command_line(check: &StatusCheck) -> String {
+    let mut words: Vec<String> = check
+        .env
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, quote(value)))
+        .collect();
+    words.extend(check.command.first().map(|program| quote(program)));
+    words.push(quote_args(&check.command_args()));
+    words.retain(|word| !word.is_empty());
+    words.join(" ")
+}
+
+/// Renders each check's command line, with its timeout, dependencies and
+/// the reason it would be skipped
+pub fn render_checks(checks: &[StatusCheck]) -> String {
+    let mut out = format!("{}\n", "Checks:".bold());
+    if checks.is_empty() {
+        out.push_str(&format!("  {}\n", "no checks enabled".dimmed()));
+    }
+    let width = checks.iter().map(|check| check.name.len()).max().unwrap_or(0);
+    for check in checks {
+        out.push_str(&format!("  {:<width$}  {}\n", check.name.bold(), command_line(check)));
+
+        let mut notes = Vec::new();
+        if let Some(timeout) = check.timeout {
+            notes.push(format!("timeout {}s", timeout.as_secs()));
+        }
+        if !check.depends_on.is_empty() {
+            notes.push(format!("after {}", check.depends_on.join(", ")));
+        }
+        if check.verbose {
+            notes.push("verbose".to_string());
+        }
+        if let Some(ref reason) = check.skip_reason {
+            notes.push(format!("skipped: {}", reason));
+        }
+        if !notes.is_empty() {
+            out.push_str(&format!("  {:<width$}  {}\n", "", notes.join(", ").dimmed()));
+        }
+    }
+    out
+}
+
+/// Problems in the configuration a run with `args` would use
+///
+/// Each layer is checked for unknown keys and bad values first; if they are
+/// all fine, the merged configuration is checked as a whole, which catches
+/// e.g. dependency cycles and missing profiles.
+pub fn validate(args: &StatusArgs) -> Result<(ConfigLayers, Vec<String>)> {
+    let layers = ConfigLayers::load(args.manifest_path.as_deref())?;
+    let mut problems: Vec<String> = layers.problems().iter().map(ToString::to_string).collect();
+    if problems.is_empty()
+        && let Err(e) = Config::new(args.clone())
+    {
+        problems.push(e.to_string());
+    }
+    Ok((layers, problems))
+}
+
+fn quote_args(args: &[String]) -> String {
+    args.iter().map(|arg| quote(arg)).collect::<Vec<_>>().join(" ")
+}
+
+/// Quotes a word for a POSIX shell if it needs it
+fn quote(word: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_=./:,+@%".contains(c);
+    if !word.is_empty() && word.chars().all(plain) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::layers::ConfigSource;
+    use crate::tools::diagnostics::strip_ansi;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[test]
+    fn test_settings_and_sources() {
+        let mut layers = ConfigLayers::default();
+        layers.push(
+            ConfigSource::Workspace,
+            "Cargo.toml",
+            toml::from_str("[tool_args]\nclippy = [\"-W\", \"clippy::pedantic\"]\n").unwrap(),
+        );
+        let args = StatusArgs {
+            clippy: true,
+            jobs: Some(2),
+            ..Default::default()
+        };
+        layers.push_args(&args);
+        let config = Config {
+            toml_config: layers.resolve().unwrap(),
+            timeouts: HashMap::from([("clippy".to_string(), Duration::from_secs(90))]),
+            layers,
+            ..Config::for_args(args)
+        };
+
+        let settings = settings(&config);
+        let find = |key: &str| settings.iter().find(|setting| setting.key == key).unwrap();
+        assert_eq!(find("checks.clippy").value, "true");
+        assert_eq!(find("checks.clippy").source, ConfigSource::Cli);
+        assert_eq!(find("checks.fmt").value, "false");
+        assert_eq!(find("max_parallel").value, "2");
+        assert_eq!(find("tool_args.clippy").value, "-W clippy::pedantic");
+        assert_eq!(find("tool_args.clippy").source, ConfigSource::Workspace);
+        assert_eq!(find("timeouts.clippy").value, "90s");
+        assert_eq!(find("target_dir").value, "shared");
+        assert_eq!(find("target_dir").source, ConfigSource::Default);
+
+        let rendered = strip_ansi(&render_settings(&settings));
+        assert!(rendered.contains("  max_parallel      2                    command line\n"), "{}", rendered);
+    }
+
+    #[test]
+    fn test_verbose_from_user_config() {
+        let mut layers = ConfigLayers::default();
+        layers.push(ConfigSource::User, "config.toml", toml::from_str("verbose = true\n").unwrap());
+        let toml_config = layers.resolve().unwrap().unwrap();
+        let mut args = StatusArgs {
+            clippy: true,
+            ..Default::default()
+        };
+        crate::config::apply_toml_config(&mut args, &toml_config, layers.selects_checks());
+        let config = Config {
+            toml_config: Some(toml_config),
+            layers,
+            ..Config::for_args(args)
+        };
+
+        let settings = settings(&config);
+        let verbose = settings.iter().find(|setting| setting.key == "verbose").unwrap();
+        assert_eq!(verbose.value, "true");
+        assert_eq!(verbose.source, ConfigSource::User);
+        assert!(config.is_tool_verbose("clippy"));
+    }
+
+    #[test]
+    fn test_command_line() {
+        let check = StatusCheck::new(
+            "Clippy",
+            vec!["cargo".to_string(), "clippy".to_string(), "--".to_string(), "-A".to_string(), "it's".to_string()],
+        )
+        .with_env("CARGO_TARGET_DIR", "target/cargo status");
+        let line = command_line(&check);
+        assert!(line.starts_with("CARGO_TARGET_DIR='target/cargo status' cargo clippy"), "{}", line);
+        assert!(line.ends_with(r"-A 'it'\''s'"), "{}", line);
+    }
+}
@@ -0,0 +1,701 @@
+//! Layered configuration
+//!
+//! Settings are merged from several sources, each overriding the ones before
+//! it: built-in defaults, the user config file, `[workspace.metadata]` of the
+//! workspace root, `[package.metadata]` of the package being checked,
+//! `CARGO_STATUS_*` environment variables and finally the command line.
+//! Tables merge key by key, so a package can override one check of the
+//! workspace's `[checks]` table and inherit the rest. Every value remembers
+//! the layer it came from.
+
+use crate::config::cli::StatusArgs;
+use crate::config::profiles::Profile;
+use crate::config::toml_config::CargoStatusConfig;
+use crate::config::user::user_config_path;
+use crate::config::{timeout_flags, TOOL_NAMES};
+use crate::error::{CargoStatusError, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use toml::{Table, Value};
+
+/// Prefix of environment variables holding settings
+pub const ENV_PREFIX: &str = "CARGO_STATUS_";
+
+/// Environment variables with the prefix that are not settings
+const INTERNAL_ENV: [&str; 1] = ["CARGO_STATUS_RUNNING"];
+
+/// Settings holding one value per tool, e.g. `CARGO_STATUS_TIMEOUTS_TEST`
+const TOOL_TABLES: [&str; 5] = ["checks", "verbose_tools", "tool_args", "timeouts", "dependencies"];
+
+/// Settings (or tables of per-tool settings) holding booleans
+const BOOLEAN_KEYS: [&str; 5] = ["sequential", "verbose", "jobserver", "checks", "verbose_tools"];
+
+/// Settings (or tables of per-tool settings) holding numbers
+const INTEGER_KEYS: [&str; 4] = ["max_parallel", "build_jobs", "max_processes", "timeouts"];
+
+/// Settings holding a single value, e.g. `CARGO_STATUS_MAX_PARALLEL`
+const SCALAR_KEYS: [&str; 8] = [
+    "sequential",
+    "verbose",
+    "max_parallel",
+    "jobserver",
+    "build_jobs",
+    "max_processes",
+    "target_dir",
+    "profile",
+];
+
+/// Where a configuration value came from, from lowest to highest precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfigSource {
+    Default,
+    User,
+    Workspace,
+    Package,
+    Env,
+    Cli,
+    /// A saved profile loaded with `--use-profile`, which replaces the
+    /// checks given on the command line
+    Profile,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Default => "default",
+            Self::User => "user config",
+            Self::Workspace => "workspace metadata",
+            Self::Package => "package metadata",
+            Self::Env => "environment",
+            Self::Cli => "command line",
+            Self::Profile => "profile",
+        })
+    }
+}
+
+/// A mistake in the settings of one layer, found by `cargo status config validate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    pub source: ConfigSource,
+    pub origin: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.origin, self.source, self.message)
+    }
+}
+
+/// Settings from one source
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub source: ConfigSource,
+    /// File or variables the settings were read from
+    pub origin: String,
+    pub table: Table,
+}
+
+/// Every source of settings for a run, lowest precedence first
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLayers {
+    pub layers: Vec<Layer>,
+}
+
+impl ConfigLayers {
+    /// Reads the user config, the `Cargo.toml` in the current directory
+    /// (the workspace root), `member`, the manifest of the package being
+    /// checked if it is another one, and the environment
+    pub fn load(member: Option<&Path>) -> Result<Self> {
+        Self::load_from(
+            user_config_path().as_deref(),
+            Path::new("Cargo.toml"),
+            member,
+            std::env::vars(),
+        )
+    }
+
+    /// Reads the layers from the given files and variables
+    pub fn load_from(
+        user_config: Option<&Path>,
+        root: &Path,
+        member: Option<&Path>,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self> {
+        let mut layers = Self::default();
+
+        if let Some(path) = user_config.filter(|path| path.is_file()) {
+            let mut table = read_table(path)?;
+            // Not a setting of the run
+            table.remove("scan");
+            layers.push(ConfigSource::User, path.display().to_string(), table);
+        }
+
+        // Outside a project there is no manifest, but a broken one is an error
+        let root_toml = root.is_file().then(|| read_table(root)).transpose()?;
+        if let Some(ref cargo_toml) = root_toml {
+            let table = metadata(cargo_toml, "workspace", root)?;
+            layers.push(ConfigSource::Workspace, root.display().to_string(), table);
+        }
+
+        // The root's package metadata belongs to the root package only
+        let package = match member {
+            Some(member) if member.is_file() => Some((read_table(member)?, member)),
+            Some(_) => None,
+            None => root_toml.map(|cargo_toml| (cargo_toml, root)),
+        };
+        if let Some((cargo_toml, path)) = package {
+            let table = metadata(&cargo_toml, "package", path)?;
+            layers.push(ConfigSource::Package, path.display().to_string(), table);
+        }
+
+        layers.push(ConfigSource::Env, format!("{}* variables", ENV_PREFIX), env_table(vars));
+        Ok(layers)
+    }
+
+    /// Adds a layer, unless it holds no settings
+    pub fn push(&mut self, source: ConfigSource, origin: impl Into<String>, table: Table) {
+        if !table.is_empty() {
+            self.layers.push(Layer {
+                source,
+                origin: origin.into(),
+                table,
+            });
+        }
+    }
+
+    /// Adds the settings given on the command line
+    pub fn push_args(&mut self, args: &StatusArgs) {
+        self.push(ConfigSource::Cli, "command line", args_table(args));
+    }
+
+    /// Adds the settings of a saved profile
+    pub fn push_profile(&mut self, name: &str, profile: &Profile) {
+        let mut table = Table::new();
+        table.insert("checks".to_string(), Value::Table(checks_table(&profile.get_enabled_tools())));
+        table.insert("sequential".to_string(), Value::Boolean(profile.sequential));
+        self.push(ConfigSource::Profile, format!("profile '{}'", name), table);
+    }
+
+    /// Merges the configuration files and environment, or `None` if none of
+    /// them has settings
+    ///
+    /// The command line and profiles are applied to the arguments instead.
+    pub fn resolve(&self) -> Result<Option<CargoStatusConfig>> {
+        let mut merged = Table::new();
+        for layer in self.layers.iter().filter(|layer| layer.source <= ConfigSource::Env) {
+            // Check each layer alone, so errors point at the right file
+            parse_config(layer.table.clone()).map_err(|e| {
+                CargoStatusError::config(format!(
+                    "Invalid settings from {} ({}): {}",
+                    layer.source,
+                    layer.origin,
+                    e.to_string().trim()
+                ))
+            })?;
+            // Unknown keys are only reported, see `problems`
+            let known: Table = layer
+                .table
+                .iter()
+                .filter(|(key, _)| is_known_key(key))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            merge(&mut merged, &known);
+        }
+        if merged.is_empty() {
+            return Ok(None);
+        }
+        parse_config(merged)
+            .map(Some)
+            .map_err(|e| CargoStatusError::config(format!("Invalid configuration: {}", e.to_string().trim())))
+    }
+
+    /// Whether the configuration decides which checks run
+    ///
+    /// A configuration file with settings does, even without a `checks`
+    /// table, which then enables the default checks. The environment only
+    /// does through `CARGO_STATUS_CHECKS_*` variables, so a stray variable or
+    /// e.g. `CARGO_STATUS_VERBOSE` alone doesn't turn on the default checks.
+    pub fn selects_checks(&self) -> bool {
+        self.layers.iter().any(|layer| match layer.source {
+            ConfigSource::User | ConfigSource::Workspace | ConfigSource::Package => {
+                layer.table.keys().any(|key| is_known_key(key))
+            }
+            ConfigSource::Env => layer.table.contains_key("checks"),
+            _ => false,
+        })
+    }
+
+    /// Unknown keys and values of the wrong type in the configuration files
+    /// and environment
+    ///
+    /// Serde ignores unknown keys, so a misspelt setting would otherwise
+    /// silently keep its default.
+    pub fn problems(&self) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+        for layer in self.layers.iter().filter(|layer| layer.source <= ConfigSource::Env) {
+            let mut report = |message: String| {
+                problems.push(ConfigProblem {
+                    source: layer.source,
+                    origin: layer.origin.clone(),
+                    message,
+                })
+            };
+            for (key, value) in &layer.table {
+                if !is_known_key(key) {
+                    report(match layer.source {
+                        ConfigSource::Env => unknown_variable(key),
+                        _ => unknown_key(key, known_keys()),
+                    });
+                    continue;
+                }
+                if let Value::Table(tools) = value
+                    && TOOL_TABLES.contains(&key.as_str())
+                {
+                    for tool in tools.keys().filter(|tool| !TOOL_NAMES.contains(&tool.as_str())) {
+                        let message = unknown_key(tool, TOOL_NAMES.iter().copied());
+                        report(format!("{} in [{}]", message, key));
+                    }
+                }
+                // One key at a time, to report every bad value
+                let single = Table::from_iter([(key.clone(), value.clone())]);
+                if let Err(e) = parse_config(single) {
+                    report(e.to_string().trim().replace('\n', " "));
+                }
+            }
+        }
+        problems
+    }
+
+    /// Source of each setting that is not a default, by dotted key
+    /// (e.g. `timeouts.test`)
+    pub fn sources(&self) -> BTreeMap<String, ConfigSource> {
+        let mut sources = BTreeMap::new();
+        for layer in &self.layers {
+            record_sources(&mut sources, "", &layer.table, layer.source);
+        }
+        sources
+    }
+
+    /// Source of a setting by dotted key, `Default` if no layer sets it
+    pub fn source(&self, key: &str) -> ConfigSource {
+        self.sources().get(key).copied().unwrap_or(ConfigSource::Default)
+    }
+}
+
+fn known_keys() -> impl Iterator<Item = &'static str> {
+    SCALAR_KEYS.iter().chain(TOOL_TABLES.iter()).copied()
+}
+
+fn is_known_key(key: &str) -> bool {
+    known_keys().any(|known| known == key)
+}
+
+/// Message for an unknown key, suggesting the closest known one
+fn unknown_key<'a>(key: &str, known: impl Iterator<Item = &'a str>) -> String {
+    match closest(key, known) {
+        Some(known) => format!("unknown key `{}` (did you mean `{}`?)", key, known),
+        None => format!("unknown key `{}`", key),
+    }
+}
+
+/// Message for a `CARGO_STATUS_*` variable that names no setting
+fn unknown_variable(key: &str) -> String {
+    let name = |key: &str| format!("{}{}", ENV_PREFIX, key.to_uppercase());
+    match closest(key, known_keys()) {
+        Some(known) => format!("unknown variable `{}` (did you mean `{}`?)", name(key), name(known)),
+        None => format!("unknown variable `{}`", name(key)),
+    }
+}
+
+/// The known key at most two edits away from `key`, if any
+fn closest<'a>(key: &str, known: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    known
+        .map(|known| (edit_distance(key, known), known))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, known)| known)
+}
+
+/// Levenshtein distance between two keys
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn parse_config(table: Table) -> std::result::Result<CargoStatusConfig, toml::de::Error> {
+    Value::Table(table).try_into()
+}
+
+fn read_table(path: &Path) -> Result<Table> {
+    let contents = std::fs::read_to_string(path).map_err(|source| CargoStatusError::Io {
+        context: format!("Failed to read {}", path.display()),
+        source,
+    })?;
+    toml::from_str(&contents)
+        .map_err(|e| CargoStatusError::config(format!("Invalid {}: {}", path.display(), e)))
+}
+
+/// The `[<section>.metadata.cargo-status]` table of a manifest
+fn metadata(cargo_toml: &Table, section: &str, path: &Path) -> Result<Table> {
+    let value = cargo_toml
+        .get(section)
+        .and_then(|section| section.get("metadata"))
+        .and_then(|metadata| metadata.get("cargo-status"));
+    match value {
+        None => Ok(Table::new()),
+        Some(Value::Table(table)) => Ok(table.clone()),
+        Some(_) => Err(CargoStatusError::config(format!(
+            "[{}.metadata.cargo-status] in {} must be a table",
+            section,
+            path.display()
+        ))),
+    }
+}
+
+/// Settings from `CARGO_STATUS_*` variables
+///
+/// `CARGO_STATUS_MAX_PARALLEL=4` sets `max_parallel`, and per-tool settings
+/// name the tool last: `CARGO_STATUS_TIMEOUTS_TEST=300`. Tool arguments are
+/// split on whitespace and dependencies on commas. Variables that name no
+/// setting are kept under their lower-cased name, where the run ignores them
+/// and [`ConfigLayers::problems`] reports them.
+pub fn env_table(vars: impl IntoIterator<Item = (String, String)>) -> Table {
+    let mut table = Table::new();
+    for (name, value) in vars {
+        let Some(key) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        if INTERNAL_ENV.contains(&name.as_str()) {
+            continue;
+        }
+        let key = key.to_lowercase();
+
+        let per_tool = TOOL_TABLES.iter().find_map(|section| {
+            let tool = key.strip_prefix(section)?.strip_prefix('_')?;
+            TOOL_NAMES.contains(&tool).then_some((*section, tool))
+        });
+        if let Some((section, tool)) = per_tool {
+            let value = match section {
+                "tool_args" => list(value.split_whitespace()),
+                "dependencies" => list(value.split(',').map(str::trim).filter(|dep| !dep.is_empty())),
+                _ => scalar(section, &value),
+            };
+            let entry = table
+                .entry(section.to_string())
+                .or_insert_with(|| Value::Table(Table::new()));
+            if let Value::Table(tools) = entry {
+                tools.insert(tool.to_string(), value);
+            }
+        } else {
+            let value = scalar(&key, &value);
+            table.insert(key, value);
+        }
+    }
+    table
+}
+
+fn list<'a>(items: impl Iterator<Item = &'a str>) -> Value {
+    Value::Array(items.map(|item| Value::String(item.to_string())).collect())
+}
+
+/// Reads a variable's value as the type of the setting `key`
+///
+/// Booleans may also be written `1`/`0` or `yes`/`no`. A value that doesn't
+/// fit is kept as a string, so the layer reports it as a wrong type.
+fn scalar(key: &str, value: &str) -> Value {
+    let value = value.trim();
+    if BOOLEAN_KEYS.contains(&key) {
+        let flag = match value.to_lowercase().as_str() {
+            "true" | "1" | "yes" => Some(true),
+            "false" | "0" | "no" => Some(false),
+            _ => None,
+        };
+        if let Some(flag) = flag {
+            return Value::Boolean(flag);
+        }
+    } else if INTEGER_KEYS.contains(&key)
+        && let Ok(number) = value.parse::<i64>()
+    {
+        return Value::Integer(number);
+    }
+    Value::String(value.to_string())
+}
+
+/// Settings given on the command line, under the keys they override
+fn args_table(args: &StatusArgs) -> Table {
+    let mut table = Table::new();
+    if args.has_tool_flags() || args.all {
+        let tools: Vec<String> = if args.all {
+            TOOL_NAMES.iter().map(|tool| tool.to_string()).collect()
+        } else {
+            args.get_enabled_tools()
+        };
+        table.insert("checks".to_string(), Value::Table(checks_table(&tools)));
+    }
+    if args.sequential {
+        table.insert("sequential".to_string(), Value::Boolean(true));
+    }
+    if args.verbose > 0 {
+        table.insert("verbose".to_string(), Value::Boolean(true));
+    }
+    if let Some(jobs) = args.jobs {
+        table.insert("max_parallel".to_string(), Value::Integer(jobs as i64));
+    }
+    if let Some(max_processes) = args.max_processes {
+        table.insert("max_processes".to_string(), Value::Integer(max_processes as i64));
+    }
+    if let Some(strategy) = args.target_dir_strategy
+        && let Ok(value) = Value::try_from(strategy)
+    {
+        table.insert("target_dir".to_string(), value);
+    }
+    if let Some(ref profile) = args.profile {
+        table.insert("profile".to_string(), Value::String(profile.clone()));
+    }
+
+    // Invalid flags are reported when the timeouts are resolved
+    let timeouts: Table = timeout_flags(args)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(tool, secs)| (tool, Value::Integer(secs as i64)))
+        .collect();
+    if !timeouts.is_empty() {
+        table.insert("timeouts".to_string(), Value::Table(timeouts));
+    }
+    table
+}
+
+/// A `checks` table enabling exactly the given tools
+fn checks_table(enabled: &[String]) -> Table {
+    TOOL_NAMES
+        .iter()
+        .map(|tool| (tool.to_string(), Value::Boolean(enabled.iter().any(|name| name == tool))))
+        .collect()
+}
+
+/// Merges `layer` into `base`, recursing into tables and replacing other values
+fn merge(base: &mut Table, layer: &Table) {
+    for (key, value) in layer {
+        match (base.get_mut(key), value) {
+            (Some(Value::Table(base)), Value::Table(layer)) => merge(base, layer),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+fn record_sources(
+    sources: &mut BTreeMap<String, ConfigSource>,
+    prefix: &str,
+    table: &Table,
+    source: ConfigSource,
+) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Table(table) => record_sources(sources, &key, table, source),
+            _ => {
+                sources.insert(key, source);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(toml: &str) -> Table {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_env_values_follow_setting_types() {
+        let mut env = env_table(vars(&[
+            ("CARGO_STATUS_VERBOSE", "1"),
+            ("CARGO_STATUS_SEQUENTIAL", "no"),
+            ("CARGO_STATUS_CHECKS_DOC", "YES"),
+            ("CARGO_STATUS_TARGET_DIR", "123"),
+            ("CARGO_STATUS_PROFILE", "true"),
+            ("CARGO_STATUS_MAX_PARALLEL", "3"),
+        ]));
+        assert_eq!(env["verbose"], Value::Boolean(true));
+        assert_eq!(env["sequential"], Value::Boolean(false));
+        assert_eq!(env["checks"]["doc"], Value::Boolean(true));
+        assert_eq!(env["target_dir"], Value::String("123".to_string()));
+        assert_eq!(env["profile"], Value::String("true".to_string()));
+        assert_eq!(env["max_parallel"], Value::Integer(3));
+
+        env.remove("target_dir");
+        let mut layers = ConfigLayers::default();
+        layers.push(ConfigSource::Env, "environment", env);
+        let config = layers.resolve().unwrap().unwrap();
+        assert!(config.verbose && !config.sequential && config.checks.doc);
+        assert_eq!(config.profile.as_deref(), Some("true"));
+
+        // A value of the wrong type is reported, not silently dropped
+        let env = env_table(vars(&[("CARGO_STATUS_JOBSERVER", "maybe")]));
+        assert_eq!(env["jobserver"], Value::String("maybe".to_string()));
+    }
+
+    #[test]
+    fn test_layers_merge_and_track_sources() {
+        let mut layers = ConfigLayers::default();
+        layers.push(ConfigSource::User, "config.toml", table("max_parallel = 2\n"));
+        layers.push(
+            ConfigSource::Workspace,
+            "Cargo.toml",
+            table("sequential = true\n[checks]\nbuild = false\ndoc = true\n[timeouts]\ntest = 600\n"),
+        );
+        layers.push(ConfigSource::Package, "api/Cargo.toml", table("[checks]\ndoc = false\n"));
+        let env = env_table(vars(&[
+            ("CARGO_STATUS_TIMEOUTS_TEST", "60"),
+            ("CARGO_STATUS_TOOL_ARGS_CLIPPY", "-W clippy::pedantic"),
+            ("CARGO_STATUS_RUNNING", "1"),
+            ("PATH", "/usr/bin"),
+        ]));
+        layers.push(ConfigSource::Env, "environment", env);
+        layers.push_args(&StatusArgs {
+            jobs: Some(4),
+            ..Default::default()
+        });
+
+        let config = layers.resolve().unwrap().unwrap();
+        assert_eq!(config.max_parallel, Some(2));
+        assert!(config.sequential);
+        assert!(!config.checks.build);
+        assert!(!config.checks.doc);
+        assert!(config.checks.test);
+        assert_eq!(config.timeouts.test, Some(60));
+        assert_eq!(config.tool_args.clippy, vec!["-W", "clippy::pedantic"]);
+
+        assert_eq!(layers.source("checks.build"), ConfigSource::Workspace);
+        assert_eq!(layers.source("checks.doc"), ConfigSource::Package);
+        assert_eq!(layers.source("timeouts.test"), ConfigSource::Env);
+        assert_eq!(layers.source("max_parallel"), ConfigSource::Cli);
+        assert_eq!(layers.source("checks.test"), ConfigSource::Default);
+    }
+
+    #[test]
+    fn test_timeout_flags() {
+        let mut layers = ConfigLayers::default();
+        layers.push(ConfigSource::Workspace, "Cargo.toml", table("[timeouts]\ntest = 600\n"));
+        layers.push_args(&StatusArgs {
+            timeout: vec!["test=30".to_string(), "120".to_string()],
+            ..Default::default()
+        });
+
+        let cli = &layers.layers[1].table["timeouts"];
+        assert_eq!(cli["test"].as_integer(), Some(30));
+        assert_eq!(cli["build"].as_integer(), Some(120));
+        assert_eq!(layers.source("timeouts.test"), ConfigSource::Cli);
+    }
+
+    #[test]
+    fn test_load_member_and_workspace_metadata() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let user = dir.path().join("config.toml");
+        let root = dir.path().join("Cargo.toml");
+        let member = dir.path().join("api/Cargo.toml");
+        std::fs::create_dir_all(dir.path().join("api")).unwrap();
+        std::fs::write(&user, "verbose = true\n[scan]\ndirs = [\"~/src\"]\n").unwrap();
+        std::fs::write(
+            &root,
+            "[package.metadata.cargo-status]\nmax_parallel = 8\n\
+             [workspace.metadata.cargo-status]\nsequential = true\n",
+        )
+        .unwrap();
+        std::fs::write(&member, "[package.metadata.cargo-status.checks]\nbuild = false\n").unwrap();
+
+        let layers = ConfigLayers::load_from(Some(&user), &root, Some(&member), Vec::new()).unwrap();
+        let config = layers.resolve().unwrap().unwrap();
+        assert!(config.verbose && config.sequential && !config.checks.build);
+        // The root package's settings don't apply to other members
+        assert_eq!(config.max_parallel, None);
+        assert!(!layers.sources().contains_key("scan.dirs"));
+
+        let layers = ConfigLayers::load_from(None, &root, None, Vec::new()).unwrap();
+        assert_eq!(layers.resolve().unwrap().unwrap().max_parallel, Some(8));
+    }
+
+    #[test]
+    fn test_invalid_layers() {
+        assert!(ConfigLayers::default().resolve().unwrap().is_none());
+
+        let mut layers = ConfigLayers::default();
+        layers.push(ConfigSource::Package, "api/Cargo.toml", table("[checks]\ntest = 3\n"));
+        let error = layers.resolve().unwrap_err().to_string();
+        assert!(error.contains("Invalid settings from package metadata (api/Cargo.toml)"), "{}", error);
+
+        // An unknown variable doesn't stop the run
+        let mut layers = ConfigLayers::default();
+        layers.push(ConfigSource::Env, "environment", env_table(vars(&[("CARGO_STATUS_PARALLEL", "2")])));
+        assert!(layers.resolve().unwrap().is_none());
+        assert!(!layers.selects_checks());
+    }
+
+    #[test]
+    fn test_problems() {
+        let mut layers = ConfigLayers::default();
+        layers.push(
+            ConfigSource::Workspace,
+            "Cargo.toml",
+            table("max_paralel = 2\n[checks]\nclipy = true\n[timeouts]\ntest = \"60\"\n"),
+        );
+        layers.push(ConfigSource::Package, "api/Cargo.toml", table("sequential = true\nfoo = 1\n"));
+        layers.push_args(&StatusArgs {
+            jobs: Some(4),
+            ..Default::default()
+        });
+
+        let problems: Vec<String> = layers.problems().iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            problems,
+            vec![
+                "Cargo.toml (workspace metadata): unknown key `clipy` (did you mean `clippy`?) in [checks]",
+                "Cargo.toml (workspace metadata): unknown key `max_paralel` (did you mean `max_parallel`?)",
+                "Cargo.toml (workspace metadata): invalid type: string \"60\", expected u64 in `timeouts.test`",
+                "api/Cargo.toml (package metadata): unknown key `foo`",
+            ]
+        );
+        assert!(ConfigLayers::default().problems().is_empty());
+
+        let mut layers = ConfigLayers::default();
+        let env = env_table(vars(&[
+            ("CARGO_STATUS_MAX_PARALEL", "2"),
+            ("CARGO_STATUS_TIMEOUTS_TSET", "60"),
+        ]));
+        layers.push(ConfigSource::Env, "environment", env);
+        let problems: Vec<String> = layers.problems().iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            problems,
+            vec![
+                "environment (environment): unknown variable `CARGO_STATUS_MAX_PARALEL` (did you mean `CARGO_STATUS_MAX_PARALLEL`?)",
+                "environment (environment): unknown variable `CARGO_STATUS_TIMEOUTS_TSET`",
+            ]
+        );
+    }
+}
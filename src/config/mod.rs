@@ -4,39 +4,88 @@
 //! - Command line arguments (CLI)
 //! - TOML configuration files (Cargo.toml)
 //! - Saved profiles (profiles.json)
+//! - User-level settings (config.toml)
+//! - `CARGO_STATUS_*` environment variables
 
 pub mod cli;
+pub mod explain;
+pub mod layers;
 pub mod profiles;
 pub mod toml_config;
+pub mod user;
 
 // Re-export commonly used types
-pub use cli::{Cli, Commands, StatusArgs};
+pub use cli::{BadgeArgs, Cli, Commands, ConfigAction, ConfigArgs, StatusArgs, StatusCommand};
 pub use profiles::{Profile, get_default_profile_name, list_profiles, load_profile, save_profile};
+pub use layers::{ConfigLayers, ConfigProblem, ConfigSource};
 pub use toml_config::{
-    build_command_with_config, load_cargo_toml_config, CargoStatusConfig, ChecksConfig,
-    ToolArgs, VerboseTools,
+    build_command_with_config, CargoStatusConfig, ChecksConfig,
+    Dependencies, TargetDirStrategy, Timeouts, ToolArgs, VerboseTools,
 };
+pub use user::{load_user_config, user_config_path, ScanConfig, UserConfig};
 
-use crate::error::Result;
-use std::collections::HashSet;
+use crate::error::{CargoStatusError, Result};
+use crate::process::accounting::DEFAULT_MAX_PROCESSES;
+use crate::report::{badge, gitlab};
+use crate::scheduler::find_cycle;
+use crate::workspace::{changed_packages, member_package, Workspace};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Names of all tools cargo-status knows how to run
+pub const TOOL_NAMES: [&str; 7] = ["fmt", "check", "clippy", "test", "build", "doc", "audit"];
+
+/// Get the display name of the check created for a tool
+pub fn tool_display_name(tool: &str) -> Option<&'static str> {
+    match tool {
+        "fmt" => Some("Format"),
+        "check" => Some("Check"),
+        "clippy" => Some("Clippy"),
+        "test" => Some("Test"),
+        "build" => Some("Build"),
+        "doc" => Some("Doc"),
+        "audit" => Some("Audit"),
+        _ => None,
+    }
+}
 
 /// Unified configuration that combines all sources
 pub struct Config {
     pub args: StatusArgs,
     pub toml_config: Option<CargoStatusConfig>,
     pub verbose_tools: HashSet<String>,
+    pub timeouts: HashMap<String, Duration>,
+    /// Packages the compiling checks are limited to by `--changed`
+    pub packages: Option<BTreeSet<String>>,
+    /// Workspace member `--manifest-path` points at, which the checks cover
+    /// instead of the whole workspace
+    pub package: Option<String>,
+    /// Every source of settings, to tell where each value came from
+    pub layers: ConfigLayers,
+    /// Layout and target directory of the workspace, if `cargo metadata`
+    /// could read it
+    pub workspace: Option<Workspace>,
+    /// Checks are only listed, as by `cargo status config show`, so building
+    /// them must not write anything
+    pub dry_run: bool,
 }
 
 impl Config {
     /// Create a new configuration by combining all sources
     pub fn new(mut args: StatusArgs) -> Result<Self> {
-        // Load TOML configuration
-        let toml_config = load_cargo_toml_config();
+        // Merge the configuration files and environment
+        let mut layers = ConfigLayers::load(args.manifest_path.as_deref())?;
+        layers.push_args(&args);
+        let toml_config = layers.resolve()?;
+        if let Some(ref config) = toml_config {
+            validate_dependencies(&config.dependencies)?;
+        }
         
         // Apply TOML configuration if no specific flags were set
         if let Some(ref config) = toml_config {
-            apply_toml_config(&mut args, config);
+            apply_toml_config(&mut args, config, layers.selects_checks());
         }
 
         // Handle profile loading
@@ -44,16 +93,43 @@ impl Config {
             let profile_name = args.profile.clone().unwrap_or_else(|| get_default_profile_name().to_string());
             let profile = load_profile(&profile_name)?;
             profile.apply_to_args(&mut args);
+            layers.push_profile(&profile_name, &profile);
         }
 
         // Parse verbose tools
         let verbose_tools = parse_verbose_tools(toml_config.as_ref(), &args);
 
-        Ok(Self {
+        // Resolve per-tool timeouts
+        let timeouts = parse_timeouts(toml_config.as_ref(), &args)?;
+
+        let package = args.manifest_path.as_deref().and_then(member_package);
+        let mut config = Self {
             args,
             toml_config,
             verbose_tools,
-        })
+            timeouts,
+            packages: None,
+            package,
+            layers,
+            workspace: Workspace::load(Path::new(".")).ok(),
+            dry_run: false,
+        };
+        config.resolve_changed_packages()?;
+        Ok(config)
+    }
+
+    /// Works out which packages `--changed` selects from the current tree
+    ///
+    /// Called again before every run in watch mode, as the tree changes.
+    pub fn resolve_changed_packages(&mut self) -> Result<()> {
+        if let Some(ref base) = self.args.changed {
+            let mut packages = changed_packages(Path::new("."), base)?;
+            if let Some(ref package) = self.package {
+                packages.retain(|changed| changed == package);
+            }
+            self.packages = Some(packages);
+        }
+        Ok(())
     }
 
     /// Check if any checks are enabled
@@ -74,16 +150,128 @@ impl Config {
         }
     }
 
+    /// Whether every tool runs in verbose mode, from `-v` or the `verbose` setting
+    pub fn is_verbose(&self) -> bool {
+        self.args.verbose > 0
+    }
+
     /// Check if a tool should run in verbose mode
     pub fn is_tool_verbose(&self, tool: &str) -> bool {
-        self.verbose_tools.contains(tool) || self.args.verbose > 0
+        self.verbose_tools.contains(tool) || self.is_verbose()
+    }
+
+    /// Maximum number of checks to run at the same time
+    ///
+    /// `--jobs` beats `max_parallel` from Cargo.toml; unset means unbounded.
+    pub fn max_parallel(&self) -> usize {
+        self.args
+            .jobs
+            .or_else(|| self.toml_config.as_ref().and_then(|config| config.max_parallel))
+            .filter(|jobs| *jobs > 0)
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Number of tokens for the shared jobserver, or `None` if it is disabled
+    pub fn jobserver_tokens(&self) -> Option<usize> {
+        let config = self.toml_config.as_ref();
+        if config.and_then(|config| config.jobserver) == Some(false) {
+            return None;
+        }
+
+        let tokens = config
+            .and_then(|config| config.build_jobs)
+            .unwrap_or_else(|| {
+                std::thread::available_parallelism()
+                    .map(|cpus| cpus.get())
+                    .unwrap_or(1)
+            });
+        Some(tokens.max(1))
+    }
+
+    /// Maximum number of child processes, or `None` if the limit is disabled
+    ///
+    /// `--max-processes` beats Cargo.toml; `0` disables the limit.
+    pub fn max_processes(&self) -> Option<usize> {
+        let limit = self
+            .args
+            .max_processes
+            .or_else(|| self.toml_config.as_ref().and_then(|config| config.max_processes))
+            .unwrap_or(DEFAULT_MAX_PROCESSES);
+        (limit > 0).then_some(limit)
+    }
+
+    /// Get the target directory strategy (`--target-dir-strategy` beats Cargo.toml)
+    pub fn target_dir_strategy(&self) -> TargetDirStrategy {
+        self.args
+            .target_dir_strategy
+            .or_else(|| self.toml_config.as_ref().map(|config| config.target_dir))
+            .unwrap_or_default()
+    }
+
+    /// Get the display names of the checks a tool depends on
+    pub fn check_dependencies(&self, tool: &str) -> Vec<String> {
+        self.toml_config
+            .as_ref()
+            .map(|config| {
+                config
+                    .dependencies
+                    .get(tool)
+                    .iter()
+                    .filter_map(|dep| tool_display_name(dep))
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Files a run writes into the workspace: reports, badges and the event
+    /// stream
+    ///
+    /// They are not sources, so writing them must not count as a change.
+    pub fn output_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = [&self.args.output, &self.args.events_output]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect();
+        if let Some(ref output) = self.args.badge {
+            paths.extend(badge::badge_paths(output, &self.get_enabled_tools()));
+        }
+        paths.push(PathBuf::from(gitlab::CODE_QUALITY_FILE));
+        paths
+    }
+
+    /// Get the timeout for a tool, if one is configured
+    pub fn tool_timeout(&self, tool: &str) -> Option<Duration> {
+        self.timeouts.get(tool).copied()
+    }
+}
+
+#[cfg(test)]
+impl Config {
+    /// Configuration made of `args` alone, without reading any files
+    pub fn for_args(args: StatusArgs) -> Self {
+        Self {
+            args,
+            toml_config: None,
+            verbose_tools: HashSet::new(),
+            timeouts: HashMap::new(),
+            packages: None,
+            package: None,
+            layers: ConfigLayers::default(),
+            workspace: None,
+            dry_run: false,
+        }
     }
 }
 
 /// Apply TOML configuration to args if no command line flags were set
-fn apply_toml_config(args: &mut StatusArgs, config: &CargoStatusConfig) {
+///
+/// The `checks` table only applies when `select_checks` is set, see
+/// [`ConfigLayers::selects_checks`].
+fn apply_toml_config(args: &mut StatusArgs, config: &CargoStatusConfig, select_checks: bool) {
     // Only apply if no specific checks were requested
-    if !args.has_tool_flags() && !args.all {
+    if select_checks && !args.has_tool_flags() && !args.all {
         // Apply default checks from config
         args.fmt = config.checks.fmt;
         args.check = config.checks.check;
@@ -98,6 +286,9 @@ fn apply_toml_config(args: &mut StatusArgs, config: &CargoStatusConfig) {
     if !args.sequential {
         args.sequential = config.sequential;
     }
+    if args.verbose == 0 && config.verbose {
+        args.verbose = 1;
+    }
 
     // Apply profile setting if not specified
     if args.profile.is_none() {
@@ -169,6 +360,92 @@ fn parse_verbose_tools(cargo_config: Option<&CargoStatusConfig>, _args: &StatusA
     verbose_tools
 }
 
+/// Ensure dependencies name known tools and contain no cycles
+fn validate_dependencies(dependencies: &Dependencies) -> Result<()> {
+    let mut edges = HashMap::new();
+    for tool in TOOL_NAMES {
+        let deps = dependencies.get(tool);
+        if let Some(unknown) = deps.iter().find(|dep| !TOOL_NAMES.contains(&dep.as_str())) {
+            return Err(CargoStatusError::config(format!(
+                "Unknown tool '{}' in dependencies of '{}' (expected one of: {})",
+                unknown,
+                tool,
+                TOOL_NAMES.join(", ")
+            )));
+        }
+        edges.insert(tool.to_string(), deps.to_vec());
+    }
+
+    match find_cycle(&edges) {
+        Some(cycle) => Err(CargoStatusError::config(format!(
+            "Dependency cycle between checks: {}",
+            cycle.join(" -> ")
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Resolve per-tool timeouts from `--timeout` flags and the TOML `timeouts` table
+///
+/// The flags beat the TOML value.
+fn parse_timeouts(
+    cargo_config: Option<&CargoStatusConfig>,
+    args: &StatusArgs,
+) -> Result<HashMap<String, Duration>> {
+    let flags = timeout_flags(args)?;
+    let mut timeouts = HashMap::new();
+    for tool in TOOL_NAMES {
+        let secs = flags
+            .get(tool)
+            .copied()
+            .or_else(|| cargo_config.and_then(|config| config.timeouts.get(tool)));
+        if let Some(secs) = secs {
+            timeouts.insert(tool.to_string(), Duration::from_secs(secs));
+        }
+    }
+
+    Ok(timeouts)
+}
+
+/// Per-tool seconds given with `--timeout`
+///
+/// `--timeout TOOL=SECS` beats a bare `--timeout SECS`, wherever they appear.
+pub(crate) fn timeout_flags(args: &StatusArgs) -> Result<HashMap<String, u64>> {
+    let mut global = None;
+    let mut per_tool = HashMap::new();
+
+    for value in &args.timeout {
+        let (tool, secs) = match value.split_once('=') {
+            Some((tool, secs)) => (Some(tool.trim()), secs.trim()),
+            None => (None, value.trim()),
+        };
+        let secs: u64 = secs.parse().map_err(|_| {
+            CargoStatusError::config(format!("Invalid timeout '{}': expected seconds", value))
+        })?;
+
+        match tool {
+            Some(tool) if TOOL_NAMES.contains(&tool) => {
+                per_tool.insert(tool.to_string(), secs);
+            }
+            Some(tool) => {
+                return Err(CargoStatusError::config(format!(
+                    "Unknown tool '{}' in --timeout (expected one of: {})",
+                    tool,
+                    TOOL_NAMES.join(", ")
+                )));
+            }
+            None => global = Some(secs),
+        }
+    }
+
+    if let Some(secs) = global {
+        for tool in TOOL_NAMES {
+            per_tool.entry(tool.to_string()).or_insert(secs);
+        }
+    }
+    Ok(per_tool)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,10 +464,117 @@ mod tests {
         toml_config.checks.fmt = true;
         toml_config.checks.clippy = true;
 
-        apply_toml_config(&mut args, &toml_config);
+        apply_toml_config(&mut args, &toml_config, true);
 
         assert!(args.fmt);
         assert!(args.clippy);
         assert!(!args.check); // Should be false by default
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_env_alone_selects_no_checks() {
+        use crate::config::layers::{env_table, ConfigSource};
+
+        let env = |vars: &[(&str, &str)]| {
+            let mut layers = ConfigLayers::default();
+            let vars = vars.iter().map(|(name, value)| (name.to_string(), value.to_string()));
+            layers.push(ConfigSource::Env, "environment", env_table(vars));
+            layers
+        };
+
+        // A stray variable is no configuration at all
+        assert!(env(&[("CARGO_STATUS_PARALEL", "2")]).resolve().unwrap().is_none());
+
+        // Settings from the environment keep the old no-config behaviour
+        let layers = env(&[("CARGO_STATUS_VERBOSE", "1")]);
+        let mut args = StatusArgs::default();
+        apply_toml_config(&mut args, &layers.resolve().unwrap().unwrap(), layers.selects_checks());
+        assert!(!args.has_tool_flags());
+        assert_eq!(args.verbose, 1);
+
+        let layers = env(&[("CARGO_STATUS_CHECKS_CLIPPY", "1")]);
+        let mut args = StatusArgs::default();
+        apply_toml_config(&mut args, &layers.resolve().unwrap().unwrap(), layers.selects_checks());
+        assert!(args.clippy);
+    }
+
+    #[test]
+    fn test_verbose_from_layers() {
+        use crate::config::layers::{env_table, ConfigSource};
+
+        let mut layers = ConfigLayers::default();
+        let env = env_table([("CARGO_STATUS_VERBOSE".to_string(), "true".to_string())]);
+        layers.push(ConfigSource::Env, "environment", env);
+        let toml_config = layers.resolve().unwrap().unwrap();
+
+        let mut args = StatusArgs {
+            clippy: true,
+            ..Default::default()
+        };
+        apply_toml_config(&mut args, &toml_config, layers.selects_checks());
+        let config = Config {
+            toml_config: Some(toml_config),
+            ..Config::for_args(args)
+        };
+        assert!(config.is_tool_verbose("clippy"));
+    }
+
+    #[test]
+    fn test_parse_timeouts() {
+        let mut toml_config = CargoStatusConfig::default();
+        toml_config.timeouts.test = Some(600);
+        toml_config.timeouts.build = Some(900);
+
+        let args = StatusArgs {
+            timeout: vec!["120".to_string(), "test=30".to_string()],
+            ..Default::default()
+        };
+
+        let timeouts = parse_timeouts(Some(&toml_config), &args).unwrap();
+        assert_eq!(timeouts.get("test"), Some(&Duration::from_secs(30)));
+        assert_eq!(timeouts.get("build"), Some(&Duration::from_secs(120)));
+
+        let invalid = StatusArgs {
+            timeout: vec!["lint=5".to_string()],
+            ..Default::default()
+        };
+        assert!(parse_timeouts(None, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_validate_dependencies() {
+        let mut dependencies = Dependencies {
+            test: vec!["build".to_string()],
+            clippy: vec!["check".to_string()],
+            ..Default::default()
+        };
+        assert!(validate_dependencies(&dependencies).is_ok());
+
+        dependencies.build = vec!["test".to_string()];
+        let err = validate_dependencies(&dependencies).unwrap_err();
+        assert!(err.to_string().contains("build -> test -> build"));
+
+        dependencies.build = vec!["lint".to_string()];
+        assert!(validate_dependencies(&dependencies).is_err());
+    }
+
+    #[test]
+    fn test_max_parallel() {
+        let toml_config = CargoStatusConfig {
+            max_parallel: Some(2),
+            ..Default::default()
+        };
+        let mut config = Config {
+            toml_config: Some(toml_config),
+            ..Config::for_args(StatusArgs::default())
+        };
+        assert_eq!(config.max_parallel(), 2);
+
+        config.args.jobs = Some(3);
+        assert_eq!(config.max_parallel(), 3);
+
+        config.args.jobs = None;
+        config.toml_config = None;
+        assert_eq!(config.max_parallel(), usize::MAX);
+    }
+}
@@ -1,7 +1,6 @@
 //! TOML configuration structures and loading logic for cargo-status
 
-use serde::Deserialize;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
 
 /// Configuration structure for Cargo.toml
 #[derive(Deserialize, Default, Debug)]
@@ -19,12 +18,31 @@ pub struct CargoStatusConfig {
     pub sequential: bool,
     #[serde(default)]
     pub verbose: bool,
+    /// Maximum number of checks to run at the same time
+    #[serde(default)]
+    pub max_parallel: Option<usize>,
+    /// Share one jobserver between all cargo processes (enabled unless `false`)
+    #[serde(default)]
+    pub jobserver: Option<bool>,
+    /// Number of jobserver tokens (defaults to the number of CPUs)
+    #[serde(default)]
+    pub build_jobs: Option<usize>,
+    /// Maximum number of processes cargo-status may have running (0 disables the limit)
+    #[serde(default)]
+    pub max_processes: Option<usize>,
+    /// How target directories are assigned to compiling checks
+    #[serde(default)]
+    pub target_dir: TargetDirStrategy,
     #[serde(default)]
     pub verbose_tools: VerboseTools,
     #[serde(default)]
     pub profile: Option<String>,
     #[serde(default)]
     pub tool_args: ToolArgs,
+    #[serde(default)]
+    pub timeouts: Timeouts,
+    #[serde(default)]
+    pub dependencies: Dependencies,
 }
 
 #[derive(Deserialize, Default, Debug, Clone)]
@@ -81,44 +99,113 @@ pub struct ToolArgs {
     pub audit: Vec<String>,
 }
 
-fn default_true() -> bool {
-    true
+impl ToolArgs {
+    /// Get the extra arguments configured for a tool
+    pub fn get(&self, tool: &str) -> &[String] {
+        match tool {
+            "fmt" => &self.fmt,
+            "check" => &self.check,
+            "clippy" => &self.clippy,
+            "test" => &self.test,
+            "build" => &self.build,
+            "doc" => &self.doc,
+            "audit" => &self.audit,
+            _ => &[],
+        }
+    }
 }
 
-/// Load cargo-status configuration from Cargo.toml
-pub fn load_cargo_toml_config() -> Option<CargoStatusConfig> {
-    // Look for Cargo.toml in current directory
-    let cargo_toml_path = Path::new("Cargo.toml");
-    if !cargo_toml_path.exists() {
-        return None;
-    }
+/// How compiling checks are assigned target directories
+///
+/// Cargo locks the target directory for the duration of a build, so checks
+/// sharing one directory end up waiting for each other.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum TargetDirStrategy {
+    /// Use cargo's default target directory for every check
+    #[default]
+    Shared,
+    /// Give each check its own directory under `target/cargo-status/<tool>`
+    PerCheck,
+    /// Share one directory per cargo profile under `target/cargo-status/<profile>`
+    Profile,
+}
+
+/// Per-tool timeouts in seconds
+#[derive(Deserialize, Default, Debug, Clone)]
+pub struct Timeouts {
+    #[serde(default)]
+    pub fmt: Option<u64>,
+    #[serde(default)]
+    pub check: Option<u64>,
+    #[serde(default)]
+    pub clippy: Option<u64>,
+    #[serde(default)]
+    pub test: Option<u64>,
+    #[serde(default)]
+    pub build: Option<u64>,
+    #[serde(default)]
+    pub doc: Option<u64>,
+    #[serde(default)]
+    pub audit: Option<u64>,
+}
 
-    // Read and parse Cargo.toml
-    let contents = std::fs::read_to_string(cargo_toml_path).ok()?;
-    let cargo_toml: toml::Value = toml::from_str(&contents).ok()?;
-
-    // Package metadata takes precedence over workspace metadata
-    // Check package first
-    if let Some(config) = cargo_toml
-        .get("package")
-        .and_then(|p| p.get("metadata"))
-        .and_then(|m| m.get("cargo-status"))
-        .and_then(|c| c.clone().try_into::<CargoStatusConfig>().ok())
-    {
-        return Some(config);
+impl Timeouts {
+    /// Get the configured timeout for a tool, in seconds
+    pub fn get(&self, tool: &str) -> Option<u64> {
+        match tool {
+            "fmt" => self.fmt,
+            "check" => self.check,
+            "clippy" => self.clippy,
+            "test" => self.test,
+            "build" => self.build,
+            "doc" => self.doc,
+            "audit" => self.audit,
+            _ => None,
+        }
     }
+}
+
+/// Checks each tool must wait for, by tool name (e.g. `test = ["build"]`)
+///
+/// A tool only runs once all of its dependencies have passed and is
+/// skipped if any of them fails.
+#[derive(Deserialize, Default, Debug, Clone)]
+pub struct Dependencies {
+    #[serde(default)]
+    pub fmt: Vec<String>,
+    #[serde(default)]
+    pub check: Vec<String>,
+    #[serde(default)]
+    pub clippy: Vec<String>,
+    #[serde(default)]
+    pub test: Vec<String>,
+    #[serde(default)]
+    pub build: Vec<String>,
+    #[serde(default)]
+    pub doc: Vec<String>,
+    #[serde(default)]
+    pub audit: Vec<String>,
+}
 
-    // Fall back to workspace metadata if no package metadata
-    if let Some(config) = cargo_toml
-        .get("workspace")
-        .and_then(|w| w.get("metadata"))
-        .and_then(|m| m.get("cargo-status"))
-        .and_then(|c| c.clone().try_into::<CargoStatusConfig>().ok())
-    {
-        return Some(config);
+impl Dependencies {
+    /// Get the dependencies declared for a tool
+    pub fn get(&self, tool: &str) -> &[String] {
+        match tool {
+            "fmt" => &self.fmt,
+            "check" => &self.check,
+            "clippy" => &self.clippy,
+            "test" => &self.test,
+            "build" => &self.build,
+            "doc" => &self.doc,
+            "audit" => &self.audit,
+            _ => &[],
+        }
     }
+}
 
-    None
+fn default_true() -> bool {
+    true
 }
 
 /// Helper function to build command with custom args from TOML config
@@ -154,4 +241,4 @@ mod tests {
         let empty_result = build_command_with_config(base.clone(), &[]);
         assert_eq!(empty_result, base);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,84 @@
+//! User-level configuration in `~/.config/cargo-status/config.toml`
+//!
+//! Holds preferences that apply to every project, such as the directories
+//! `cargo status --scan` looks for workspaces in.
+
+use crate::error::{CargoStatusError, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CONFIG_DIR_NAME: &str = "cargo-status";
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Contents of the user config file
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UserConfig {
+    #[serde(default)]
+    pub scan: ScanConfig,
+}
+
+/// The `[scan]` table
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ScanConfig {
+    /// Directories searched for workspaces when `--scan` is given no directory
+    #[serde(default)]
+    pub dirs: Vec<PathBuf>,
+}
+
+/// Path of the user config file, whether or not it exists
+pub fn user_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+}
+
+/// Loads the user config file, or the defaults if there is none
+pub fn load_user_config() -> Result<UserConfig> {
+    match user_config_path() {
+        Some(path) if path.exists() => UserConfig::load(&path),
+        _ => Ok(UserConfig::default()),
+    }
+}
+
+impl UserConfig {
+    /// Reads and parses a user config file
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|source| CargoStatusError::Io {
+            context: format!("Failed to read {}", path.display()),
+            source,
+        })?;
+        Self::parse(&contents)
+            .map_err(|e| CargoStatusError::config(format!("Invalid {}: {}", path.display(), e)))
+    }
+
+    /// Parses the contents of a user config file, expanding `~` in paths
+    pub fn parse(contents: &str) -> std::result::Result<Self, toml::de::Error> {
+        let mut config: Self = toml::from_str(contents)?;
+        for dir in &mut config.scan.dirs {
+            *dir = expand_home(dir);
+        }
+        Ok(config)
+    }
+}
+
+/// Replaces a leading `~` with the home directory
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scan_dirs() {
+        let config = UserConfig::parse("[scan]\ndirs = [\"~/src\", \"/opt/repos\"]\n").unwrap();
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(config.scan.dirs, vec![home.join("src"), PathBuf::from("/opt/repos")]);
+
+        assert!(UserConfig::parse("").unwrap().scan.dirs.is_empty());
+        assert!(UserConfig::parse("[scan]\ndirs = \"~/src\"\n").is_err());
+    }
+}
@@ -12,12 +12,40 @@ use crossterm::{
 };
 use std::collections::HashMap;
 use std::io::{self, Write};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
+/// Display state of a single check
+#[derive(Debug, Clone)]
+struct CheckState {
+    row: u16,
+    status: CheckStatus,
+    start_time: Instant,
+    /// Latest output line or progress message, shown next to the spinner
+    activity: Option<String>,
+    /// Set while the check is blocked rather than doing work
+    waiting: Option<String>,
+    /// Whether the result was replayed from the result cache
+    cached: bool,
+}
+
+impl CheckState {
+    fn new(row: u16) -> Self {
+        Self {
+            row,
+            status: CheckStatus::Pending,
+            start_time: Instant::now(),
+            activity: None,
+            waiting: None,
+            cached: false,
+        }
+    }
+}
+
 /// Type alias for check state storage
-type CheckStateMap = HashMap<String, (u16, CheckStatus, Instant)>;
+type CheckStateMap = HashMap<String, CheckState>;
 
 /// Status of a check execution
 #[derive(Debug, Clone, PartialEq)]
@@ -43,6 +71,43 @@ pub enum CheckStatus {
         reason: String,
         duration: Duration,
     },
+    TimedOut {
+        timeout: Duration,
+        duration: Duration,
+    },
+    Cancelled {
+        duration: Duration,
+    },
+    Skipped {
+        reason: String,
+    },
+}
+
+impl CheckStatus {
+    /// Whether the check has reached a final state
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, CheckStatus::Pending | CheckStatus::Running { .. })
+    }
+
+    /// How long the check ran, once it has finished
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            CheckStatus::Success { duration, .. }
+            | CheckStatus::Warning { duration, .. }
+            | CheckStatus::Error { duration, .. }
+            | CheckStatus::Failed { duration, .. }
+            | CheckStatus::TimedOut { duration, .. }
+            | CheckStatus::Cancelled { duration } => Some(*duration),
+            CheckStatus::Pending | CheckStatus::Running { .. } | CheckStatus::Skipped { .. } => None,
+        }
+    }
+}
+
+/// Output stream a line of tool output was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
 }
 
 /// Event types for status updates
@@ -50,7 +115,18 @@ pub enum CheckStatus {
 pub enum StatusEvent {
     CheckStarted { name: String },
     CheckProgress { name: String, message: String },
+    CheckOutput {
+        name: String,
+        stream: OutputStream,
+        line: String,
+    },
+    /// The check is blocked, e.g. waiting for cargo's build directory lock
+    CheckWaiting { name: String, reason: String },
+    /// The check's result is replayed from the result cache
+    CheckCached { name: String },
     CheckCompleted { name: String, status: CheckStatus },
+    /// The run was interrupted; in-flight checks are being cancelled
+    Cancelled,
     AllCompleted,
 }
 
@@ -63,6 +139,9 @@ pub struct InteractiveDisplay {
     event_sender: mpsc::UnboundedSender<StatusEvent>,
     spinner_frames: Vec<&'static str>,
     base_row: u16,
+    cancelled: bool,
+    /// Print nothing, e.g. while a report is written to stdout
+    silent: bool,
 }
 
 impl InteractiveDisplay {
@@ -79,9 +158,17 @@ impl InteractiveDisplay {
             event_sender,
             spinner_frames: vec!["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
             base_row: 2,
+            cancelled: false,
+            silent: false,
         }
     }
 
+    /// Suppresses all terminal output while still consuming events
+    pub fn with_silent(mut self, silent: bool) -> Self {
+        self.silent = silent;
+        self
+    }
+
     /// Returns a clone of the event sender for use by check runners
     pub fn event_sender(&self) -> mpsc::UnboundedSender<StatusEvent> {
         self.event_sender.clone()
@@ -132,8 +219,18 @@ impl InteractiveDisplay {
 
     /// Initialize the display with tool names
     pub fn initialize(&mut self, tool_names: Vec<String>) -> io::Result<()> {
+        if self.silent {
+            return Ok(());
+        }
+
         if !self.is_interactive {
             self.print_fallback_header(&tool_names);
+
+            // Track states so an interrupted run can report unfinished checks
+            let mut states = self.check_states.lock().unwrap();
+            for (index, name) in tool_names.iter().enumerate() {
+                states.insert(name.clone(), CheckState::new(self.base_row + index as u16));
+            }
             return Ok(());
         }
 
@@ -151,7 +248,7 @@ impl InteractiveDisplay {
         let mut states = self.check_states.lock().unwrap();
         for (index, name) in tool_names.iter().enumerate() {
             let row = self.base_row + index as u16;
-            states.insert(name.clone(), (row, CheckStatus::Pending, Instant::now()));
+            states.insert(name.clone(), CheckState::new(row));
 
             // Draw initial status line
             self.draw_check_line(row, &format!("  {} {} Pending", "◦".dimmed(), name.bold()))?;
@@ -161,6 +258,41 @@ impl InteractiveDisplay {
         Ok(())
     }
 
+    /// Prepares the display for another run of the same checks
+    ///
+    /// Rows keep their previous result until the check starts again, so the
+    /// layout stays put; only the summary below them is replaced.
+    pub fn restart(&mut self, changed: &[PathBuf]) -> io::Result<()> {
+        self.start_time = Instant::now();
+        self.cancelled = false;
+        let mut states = self.check_states.lock().unwrap();
+        for state in states.values_mut() {
+            state.status = CheckStatus::Pending;
+            state.start_time = self.start_time;
+            state.activity = None;
+            state.waiting = None;
+        }
+        if self.silent {
+            return Ok(());
+        }
+
+        let message = format!("Change detected in {}, re-running checks...", describe_changes(changed));
+        if self.is_interactive {
+            let max_row = states.values().map(|state| state.row).max().unwrap_or(0);
+            execute!(
+                io::stdout(),
+                MoveTo(0, max_row + 2),
+                Clear(ClearType::FromCursorDown),
+                cursor::Hide,
+                Print(message.dimmed())
+            )?;
+            io::stdout().flush()?;
+        } else {
+            println!("\n{}", message.bold().blue());
+        }
+        Ok(())
+    }
+
     /// Draw a line at a specific row
     fn draw_check_line(&self, row: u16, content: &str) -> io::Result<()> {
         execute!(
@@ -184,6 +316,15 @@ impl InteractiveDisplay {
 
     /// Main event processing loop
     pub async fn run(&mut self) -> io::Result<()> {
+        if self.silent {
+            while let Some(event) = self.event_receiver.recv().await {
+                if matches!(event, StatusEvent::AllCompleted) {
+                    break;
+                }
+            }
+            return Ok(());
+        }
+
         if !self.is_interactive {
             return self.run_fallback_mode().await;
         }
@@ -197,21 +338,15 @@ impl InteractiveDisplay {
                 tokio::time::sleep(Duration::from_millis(80)).await;
 
                 let states = states_clone.lock().unwrap();
-                for (name, (row, status, start_time)) in states.iter() {
-                    if let CheckStatus::Running { .. } = status {
+                for (name, state) in states.iter() {
+                    if let CheckStatus::Running { .. } = state.status {
                         let spinner = spinner_frames[frame_idx];
-                        let elapsed = start_time.elapsed();
-                        let line = format!(
-                            "  {} {} Running... {}",
-                            spinner.blue(),
-                            name.bold(),
-                            format_duration(elapsed).dimmed()
-                        );
+                        let line = running_line(name, spinner, state);
 
                         // Update the line
                         let _ = execute!(
                             io::stdout(),
-                            MoveTo(0, *row),
+                            MoveTo(0, state.row),
                             Clear(ClearType::CurrentLine),
                             Print(line)
                         );
@@ -234,9 +369,23 @@ impl InteractiveDisplay {
                 StatusEvent::CheckProgress { name, message } => {
                     self.handle_check_progress(&name, &message)?;
                 }
+                StatusEvent::CheckOutput { name, line, .. } => {
+                    self.handle_check_output(&name, &line);
+                }
+                StatusEvent::CheckWaiting { name, reason } => {
+                    self.handle_check_waiting(&name, &reason);
+                }
+                StatusEvent::CheckCached { name } => {
+                    if let Some(state) = self.check_states.lock().unwrap().get_mut(&name) {
+                        state.cached = true;
+                    }
+                }
                 StatusEvent::CheckCompleted { name, status } => {
                     self.handle_check_completed(&name, status)?;
                 }
+                StatusEvent::Cancelled => {
+                    self.cancelled = true;
+                }
                 StatusEvent::AllCompleted => {
                     spinner_handle.abort();
                     self.handle_all_completed()?;
@@ -253,54 +402,42 @@ impl InteractiveDisplay {
         while let Some(event) = self.event_receiver.recv().await {
             match event {
                 StatusEvent::CheckStarted { name } => {
+                    if let Some(state) = self.check_states.lock().unwrap().get_mut(&name) {
+                        state.status = CheckStatus::Running {
+                            start_time: Instant::now(),
+                        };
+                    }
                     print!("  {} {} ... ", "◦".blue(), name);
                     io::stdout().flush()?;
                 }
-                StatusEvent::CheckCompleted { name: _, status } => match status {
-                    CheckStatus::Success { duration, warnings } => {
-                        if warnings > 0 {
-                            println!(
-                                "{} ({} warnings, {})",
-                                "✓".green(),
-                                warnings,
-                                format_duration(duration).dimmed()
-                            );
-                        } else {
-                            println!("{} ({})", "✓".green(), format_duration(duration).dimmed());
-                        }
-                    }
-                    CheckStatus::Warning { warnings, duration } => {
-                        println!(
-                            "{} ({} warnings, {})",
-                            "⚠".yellow(),
-                            warnings,
-                            format_duration(duration).dimmed()
-                        );
-                    }
-                    CheckStatus::Error {
-                        errors,
-                        warnings,
-                        duration,
-                    } => {
-                        println!(
-                            "{} ({} errors, {} warnings, {})",
-                            "✗".red(),
-                            errors,
-                            warnings,
-                            format_duration(duration).dimmed()
-                        );
+                StatusEvent::CheckCompleted { name, status } => {
+                    let mut states = self.check_states.lock().unwrap();
+                    let started = states
+                        .get(&name)
+                        .is_some_and(|state| matches!(state.status, CheckStatus::Running { .. }));
+                    if let Some(state) = states.get_mut(&name) {
+                        state.status = status.clone();
                     }
-                    CheckStatus::Failed { reason, duration } => {
-                        println!(
-                            "{} ({}, {})",
-                            "✗".red(),
-                            reason.red(),
-                            format_duration(duration).dimmed()
-                        );
+                    drop(states);
+
+                    // Checks that never started (skipped or cancelled) need their name printed
+                    if !started {
+                        print!("  {} {} ... ", "◦".dimmed(), name);
                     }
-                    _ => {}
-                },
+                    print_fallback_result(status);
+                }
+                StatusEvent::CheckCached { .. } => {
+                    print!("{} ", "cached".dimmed());
+                    io::stdout().flush()?;
+                }
+                StatusEvent::Cancelled => {
+                    self.cancelled = true;
+                    println!("\n{}", "Interrupted, cancelling running checks...".yellow());
+                }
                 StatusEvent::AllCompleted => {
+                    if self.cancelled {
+                        self.handle_all_completed()?;
+                    }
                     break;
                 }
                 _ => {}
@@ -312,10 +449,13 @@ impl InteractiveDisplay {
     /// Handle check started event
     fn handle_check_started(&self, name: &str) -> io::Result<()> {
         let mut states = self.check_states.lock().unwrap();
-        if let Some((row, _, _)) = states.get(name).cloned() {
+        if let Some(state) = states.get_mut(name) {
             let start_time = Instant::now();
-            let status = CheckStatus::Running { start_time };
-            states.insert(name.to_string(), (row, status, start_time));
+            state.status = CheckStatus::Running { start_time };
+            state.start_time = start_time;
+            state.activity = None;
+            state.cached = false;
+            let row = state.row;
             
             // Draw initial running state
             let line = format!(
@@ -332,27 +472,48 @@ impl InteractiveDisplay {
 
     /// Handle check progress event
     fn handle_check_progress(&self, name: &str, message: &str) -> io::Result<()> {
-        let states = self.check_states.lock().unwrap();
-        if let Some((row, _, start_time)) = states.get(name) {
-            let elapsed = start_time.elapsed();
-            self.draw_check_line(
-                *row,
-                &format!(
-                    "  {} {} {} {}",
-                    "⠼".blue(),
-                    name.bold(),
-                    message.dimmed(),
-                    format_duration(elapsed).dimmed()
-                ),
-            )?;
+        let mut states = self.check_states.lock().unwrap();
+        if let Some(state) = states.get_mut(name) {
+            state.activity = Some(message.to_string());
+            let line = running_line(name, "⠼", state);
+            let row = state.row;
+            drop(states); // Release lock before drawing
+            self.draw_check_line(row, &line)?;
         }
         Ok(())
     }
 
+    /// Handle a line of tool output
+    ///
+    /// The latest non-empty line is shown next to the spinner on the next redraw.
+    fn handle_check_output(&self, name: &str, line: &str) {
+        let text = crate::tools::diagnostics::strip_ansi(line);
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+
+        let mut states = self.check_states.lock().unwrap();
+        if let Some(state) = states.get_mut(name) {
+            state.activity = Some(text.to_string());
+            // Any further output means the check is no longer blocked
+            state.waiting = None;
+        }
+    }
+
+    /// Handle a check becoming blocked
+    fn handle_check_waiting(&self, name: &str, reason: &str) {
+        let mut states = self.check_states.lock().unwrap();
+        if let Some(state) = states.get_mut(name) {
+            state.waiting = Some(reason.to_string());
+        }
+    }
+
     /// Handle check completed event
     fn handle_check_completed(&self, name: &str, status: CheckStatus) -> io::Result<()> {
         let mut states = self.check_states.lock().unwrap();
-        if let Some((row, _, start_time)) = states.get(name).cloned() {
+        if let Some(state) = states.get_mut(name) {
+            let row = state.row;
             let line = match &status {
                 CheckStatus::Success { duration, warnings } => {
                     if *warnings > 0 {
@@ -400,14 +561,31 @@ impl InteractiveDisplay {
                         "  {} {} ({}, {})",
                         "✗".red(),
                         name.bold(),
-                        reason.red(),
+                        first_line(reason).red(),
                         format_duration(*duration).dimmed()
                     )
                 }
+                CheckStatus::TimedOut { timeout, .. } => {
+                    format!(
+                        "  {} {} ({})",
+                        "⏱".red(),
+                        name.bold(),
+                        format!("timed out after {}", format_duration(*timeout)).red()
+                    )
+                }
+                CheckStatus::Cancelled { .. } => cancelled_line(name),
+                CheckStatus::Skipped { reason } => skipped_line(name, reason),
                 _ => return Ok(()),
             };
 
-            states.insert(name.to_string(), (row, status, start_time));
+            let line = if state.cached {
+                format!("{} {}", line, "· cached".dimmed())
+            } else {
+                line
+            };
+            state.status = status;
+            state.activity = None;
+            drop(states); // Release lock before drawing
             self.draw_check_line(row, &line)?;
         }
         Ok(())
@@ -415,20 +593,80 @@ impl InteractiveDisplay {
 
     /// Handle all checks completed
     fn handle_all_completed(&self) -> io::Result<()> {
+        let mut states = self.check_states.lock().unwrap();
+        let total_checks = states.len();
+        let mut unfinished = Vec::new();
+
+        if self.cancelled {
+            // Checks that never reported back were cut short by the interrupt
+            for (name, state) in states.iter_mut() {
+                if !state.status.is_finished() {
+                    state.status = CheckStatus::Cancelled {
+                        duration: state.start_time.elapsed(),
+                    };
+                    unfinished.push((state.row, name.clone()));
+                }
+            }
+            unfinished.sort();
+        }
+        let completed = states
+            .values()
+            .filter(|state| !matches!(state.status, CheckStatus::Cancelled { .. }))
+            .count();
+
         if self.is_interactive {
+            for (row, name) in &unfinished {
+                self.draw_check_line(*row, &cancelled_line(name))?;
+            }
+
             // Move cursor below all checks
-            let states = self.check_states.lock().unwrap();
-            let max_row = states.values().map(|(row, _, _)| *row).max().unwrap_or(0);
+            let max_row = states.values().map(|state| state.row).max().unwrap_or(0);
+
+            execute!(
+                io::stdout(),
+                MoveTo(0, max_row + 2),
+                Clear(ClearType::FromCursorDown),
+                cursor::Show
+            )?;
 
-            execute!(io::stdout(), MoveTo(0, max_row + 2), cursor::Show)?;
+            // Rows only have room for the first line of a failure reason
+            let mut failures: Vec<_> = states
+                .iter()
+                .filter_map(|(name, state)| match state.status {
+                    CheckStatus::Failed { ref reason, .. } if reason.contains('\n') => {
+                        Some((state.row, name, reason))
+                    }
+                    _ => None,
+                })
+                .collect();
+            failures.sort();
+            for (_, name, reason) in failures {
+                println!("{} {}", format!("{}:", name).bold(), first_line(reason).red());
+                print_failure_details(reason);
+            }
+        } else {
+            for (_, name) in &unfinished {
+                println!("{}", cancelled_line(name));
+            }
         }
+        drop(states);
 
         let total_duration = self.start_time.elapsed();
-        println!(
-            "{} Completed in {}",
-            "Summary:".bold().underline(),
-            format_duration(total_duration).bold()
-        );
+        if self.cancelled {
+            println!(
+                "{} Cancelled after {} ({} of {} checks completed)",
+                "Summary:".bold().underline(),
+                format_duration(total_duration).bold(),
+                completed,
+                total_checks
+            );
+        } else {
+            println!(
+                "{} Completed in {}",
+                "Summary:".bold().underline(),
+                format_duration(total_duration).bold()
+            );
+        }
 
         Ok(())
     }
@@ -443,7 +681,7 @@ impl InteractiveDisplay {
 
     /// Cleanup on drop or error
     pub fn cleanup(&self) -> io::Result<()> {
-        if self.is_interactive {
+        if self.is_interactive && !self.silent {
             execute!(io::stdout(), cursor::Show)?;
         }
         Ok(())
@@ -462,8 +700,154 @@ impl Drop for InteractiveDisplay {
     }
 }
 
+/// Print the result of a completed check in non-interactive mode
+fn print_fallback_result(status: CheckStatus) {
+    match status {
+        CheckStatus::Success { duration, warnings } => {
+            if warnings > 0 {
+                println!(
+                    "{} ({} warnings, {})",
+                    "✓".green(),
+                    warnings,
+                    format_duration(duration).dimmed()
+                );
+            } else {
+                println!("{} ({})", "✓".green(), format_duration(duration).dimmed());
+            }
+        }
+        CheckStatus::Warning { warnings, duration } => {
+            println!(
+                "{} ({} warnings, {})",
+                "⚠".yellow(),
+                warnings,
+                format_duration(duration).dimmed()
+            );
+        }
+        CheckStatus::Error {
+            errors,
+            warnings,
+            duration,
+        } => {
+            println!(
+                "{} ({} errors, {} warnings, {})",
+                "✗".red(),
+                errors,
+                warnings,
+                format_duration(duration).dimmed()
+            );
+        }
+        CheckStatus::Failed { reason, duration } => {
+            println!(
+                "{} ({}, {})",
+                "✗".red(),
+                first_line(&reason).red(),
+                format_duration(duration).dimmed()
+            );
+            print_failure_details(&reason);
+        }
+        CheckStatus::TimedOut { timeout, .. } => {
+            println!(
+                "{} ({})",
+                "⏱".red(),
+                format!("timed out after {}", format_duration(timeout)).red()
+            );
+        }
+        CheckStatus::Cancelled { .. } => {
+            println!("{} ({})", "⊘".yellow(), "cancelled".yellow());
+        }
+        CheckStatus::Skipped { reason } => {
+            println!("{} ({})", "↷".dimmed(), format!("skipped: {}", reason).dimmed());
+        }
+        _ => {}
+    }
+}
+
+/// Get the first line of a possibly multi-line failure reason
+fn first_line(reason: &str) -> &str {
+    reason.lines().next().unwrap_or_default()
+}
+
+/// Print the lines of a failure reason after the first, indented
+fn print_failure_details(reason: &str) {
+    for line in reason.lines().skip(1) {
+        println!("    {}", line.dimmed());
+    }
+}
+
+/// Names the changed files, e.g. `src/lib.rs and 2 other files`
+fn describe_changes(changed: &[PathBuf]) -> String {
+    match changed {
+        [] => "the workspace".to_string(),
+        [file] => file.display().to_string(),
+        [file, rest @ ..] => format!(
+            "{} and {} other file{}",
+            file.display(),
+            rest.len(),
+            if rest.len() == 1 { "" } else { "s" }
+        ),
+    }
+}
+
+/// Build the status line for a cancelled check
+fn cancelled_line(name: &str) -> String {
+    format!("  {} {} ({})", "⊘".yellow(), name.bold(), "cancelled".yellow())
+}
+
+/// Build the status line for a check skipped because a dependency did not pass
+fn skipped_line(name: &str, reason: &str) -> String {
+    format!(
+        "  {} {} ({})",
+        "↷".dimmed(),
+        name.bold(),
+        format!("skipped: {}", reason).dimmed()
+    )
+}
+
+/// Build the status line for a running check
+fn running_line(name: &str, spinner: &str, state: &CheckState) -> String {
+    if let Some(ref reason) = state.waiting {
+        return format!(
+            "  {} {} {} {}",
+            "⧗".yellow(),
+            name.bold(),
+            capitalize(reason).yellow(),
+            format_duration(state.start_time.elapsed()).dimmed()
+        );
+    }
+
+    let mut line = format!(
+        "  {} {} Running... {}",
+        spinner.blue(),
+        name.bold(),
+        format_duration(state.start_time.elapsed()).dimmed()
+    );
+
+    if let Some(ref activity) = state.activity {
+        // Keep the activity on a single terminal row
+        let width = crossterm::terminal::size()
+            .map(|(cols, _)| cols as usize)
+            .unwrap_or(80);
+        let used = name.chars().count() + 25;
+        if width > used + 4 {
+            let text: String = activity.chars().take(width - used).collect();
+            line.push_str(&format!("  {}", text.dimmed()));
+        }
+    }
+
+    line
+}
+
+/// Uppercase the first letter of a message
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 /// Format duration in a human-readable way
-fn format_duration(duration: Duration) -> String {
+pub fn format_duration(duration: Duration) -> String {
     let millis = duration.as_millis();
     if millis < 1000 {
         format!("{}ms", millis)
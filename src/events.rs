@@ -0,0 +1,204 @@
+//! Live stream of status events for editors and dashboards
+//!
+//! With `--events ndjson`, every [`StatusEvent`] is written as one JSON
+//! object per line as soon as it happens, before it is passed on to the
+//! terminal display. Each line carries a timestamp and the time elapsed
+//! since the run started.
+
+use crate::display::{CheckStatus, OutputStream, StatusEvent};
+use crate::error::{CargoStatusError, Result};
+use crate::report::{rfc3339, status_counts, status_name};
+use crate::tools::diagnostics::strip_ansi;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Format of the event stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventFormat {
+    /// One JSON object per line
+    Ndjson,
+}
+
+/// Opens the destination of the event stream, stdout unless a file is given
+pub fn open_output(path: Option<&Path>) -> Result<Box<dyn Write + Send>> {
+    match path {
+        Some(path) => {
+            let file = File::create(path).map_err(|source| CargoStatusError::Io {
+                context: format!("Failed to create event stream {}", path.display()),
+                source,
+            })?;
+            Ok(Box::new(file))
+        }
+        None => Ok(Box::new(io::stdout())),
+    }
+}
+
+/// Writes every event to `out` and forwards it to `display`
+///
+/// Returns the sender checks should report to, and a handle that finishes
+/// once every clone of it has been dropped. In watch mode the stream spans
+/// several runs, each ending with `AllCompleted`.
+pub fn tee_events(
+    display: mpsc::UnboundedSender<StatusEvent>,
+    mut out: Box<dyn Write + Send>,
+) -> (mpsc::UnboundedSender<StatusEvent>, JoinHandle<()>) {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let started = Instant::now();
+
+    let handle = tokio::spawn(async move {
+        while let Some(event) = receiver.recv().await {
+            let line = event_json(&event, SystemTime::now(), started.elapsed());
+            // Flush each line so consumers see events as they happen
+            let _ = writeln!(out, "{}", line).and_then(|_| out.flush());
+            let _ = display.send(event);
+        }
+    });
+
+    (sender, handle)
+}
+
+/// Serializes an event as a single-line JSON object
+pub fn event_json(event: &StatusEvent, timestamp: SystemTime, elapsed: Duration) -> String {
+    let mut value = match event {
+        StatusEvent::CheckStarted { name } => json!({
+            "event": "check_started",
+            "check": name,
+        }),
+        StatusEvent::CheckProgress { name, message } => json!({
+            "event": "check_progress",
+            "check": name,
+            "message": message,
+        }),
+        StatusEvent::CheckOutput { name, stream, line } => json!({
+            "event": "check_output",
+            "check": name,
+            "stream": match stream {
+                OutputStream::Stdout => "stdout",
+                OutputStream::Stderr => "stderr",
+            },
+            "line": strip_ansi(line),
+        }),
+        StatusEvent::CheckWaiting { name, reason } => json!({
+            "event": "check_waiting",
+            "check": name,
+            "reason": reason,
+        }),
+        StatusEvent::CheckCached { name } => json!({
+            "event": "check_cached",
+            "check": name,
+        }),
+        StatusEvent::CheckCompleted { name, status } => completed_json(name, status),
+        StatusEvent::Cancelled => json!({ "event": "cancelled" }),
+        StatusEvent::AllCompleted => json!({ "event": "all_completed" }),
+    };
+
+    value["timestamp"] = json!(rfc3339(timestamp));
+    value["elapsed_ms"] = json!(elapsed.as_millis() as u64);
+    value.to_string()
+}
+
+fn completed_json(name: &str, status: &CheckStatus) -> Value {
+    let (errors, warnings) = status_counts(status);
+    let mut value = json!({
+        "event": "check_completed",
+        "check": name,
+        "status": status_name(status),
+        "errors": errors,
+        "warnings": warnings,
+    });
+    if let Some(duration) = status.duration() {
+        value["duration_ms"] = json!(duration.as_millis() as u64);
+    }
+    match status {
+        CheckStatus::Failed { reason, .. } | CheckStatus::Skipped { reason } => {
+            value["reason"] = json!(reason);
+        }
+        CheckStatus::TimedOut { timeout, .. } => {
+            value["timeout_ms"] = json!(timeout.as_millis() as u64);
+        }
+        _ => {}
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::UNIX_EPOCH;
+
+    /// Writer whose contents stay readable after it has been moved into a task
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_event_json() {
+        let event = StatusEvent::CheckCompleted {
+            name: "Clippy".to_string(),
+            status: CheckStatus::Error {
+                errors: 2,
+                warnings: 1,
+                duration: Duration::from_millis(1500),
+            },
+        };
+        let line = event_json(&event, UNIX_EPOCH, Duration::from_millis(1600));
+        let value: Value = serde_json::from_str(&line).unwrap();
+
+        assert!(!line.contains('\n'));
+        assert_eq!(value["event"], "check_completed");
+        assert_eq!(value["check"], "Clippy");
+        assert_eq!(value["status"], "error");
+        assert_eq!(value["errors"], 2);
+        assert_eq!(value["duration_ms"], 1500);
+        assert_eq!(value["elapsed_ms"], 1600);
+        assert_eq!(value["timestamp"], "1970-01-01T00:00:00.000Z");
+    }
+
+    #[tokio::test]
+    async fn test_tee_events_writes_and_forwards() {
+        let buffer = SharedBuffer::default();
+        let (display, mut display_events) = mpsc::unbounded_channel();
+        let (sender, handle) = tee_events(display, Box::new(buffer.clone()));
+
+        sender
+            .send(StatusEvent::CheckStarted {
+                name: "Check".to_string(),
+            })
+            .unwrap();
+        sender.send(StatusEvent::AllCompleted).unwrap();
+        drop(sender);
+        handle.await.unwrap();
+
+        let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let events: Vec<Value> = written
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["event"], "check_started");
+        assert_eq!(events[1]["event"], "all_completed");
+
+        assert!(matches!(
+            display_events.recv().await,
+            Some(StatusEvent::CheckStarted { .. })
+        ));
+        assert!(matches!(display_events.recv().await, Some(StatusEvent::AllCompleted)));
+    }
+}
@@ -2,9 +2,20 @@ pub mod cache;
 pub mod config;
 pub mod display;
 pub mod error;
+pub mod events;
+pub mod process;
+pub mod report;
+pub mod scan;
+pub mod scheduler;
 pub mod tools;
+pub mod watch;
+pub mod workspace;
 
 pub use config::{Config, StatusArgs};
 pub use display::{CheckStatus, InteractiveDisplay, StatusEvent};
 pub use error::{CargoStatusError, Result};
-pub use tools::{StatusCheck, create_all_checks};
+pub use report::{ReportFormat, RunReport};
+pub use scheduler::Scheduler;
+pub use tools::{
+    CheckResult, Diagnostic, DiagnosticLevel, StatusCheck, TestCounts, create_all_checks,
+};
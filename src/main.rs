@@ -4,12 +4,30 @@
 //! in parallel and provides unified status reporting with real-time feedback.
 
 use cargo_status::{
-    config::{list_profiles, save_profile, Cli, Commands, Config},
-    create_all_checks, InteractiveDisplay, Result, StatusEvent,
+    cache::results::ResultCache,
+    config::{
+        explain, list_profiles, save_profile, BadgeArgs, Cli, Commands, Config, ConfigAction,
+        StatusArgs, StatusCommand,
+    },
+    events,
+    process::{accounting::ProcessTable, create_jobserver},
+    report::{badge, ci::CiProvider, github, gitlab, last_run_path, packages},
+    scan,
+    tools::registry::ToolRegistry,
+    watch::Watcher,
+    workspace::{Project, Workspace},
+    create_all_checks, CheckResult, CheckStatus, InteractiveDisplay, Result, RunReport,
+    Scheduler, StatusEvent,
 };
 use clap::Parser;
+use colored::Colorize;
 use std::env;
-use tokio::task::JoinSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
 
 /// Show help information when no specific checks are enabled
 fn show_help() {
@@ -25,7 +43,26 @@ fn show_help() {
     println!("  -u, --audit            Run cargo audit (security vulnerabilities)");
     println!("  -a, --all              Run all available checks");
     println!("      --sequential       Force sequential execution");
+    println!("  -j, --jobs <N>         Run at most N checks at the same time");
     println!("  -v, --verbose          Show output for all tools");
+    println!("      --target-dir-strategy <shared|per-check|profile>");
+    println!("                         Where compiling checks put build artifacts");
+    println!("      --timeout [TOOL=]SECS  Kill checks that run too long");
+    println!("      --format <json|junit|sarif|gitlab|markdown|html>");
+    println!("                         Write a machine-readable report to stdout");
+    println!("      --output <FILE>    Write the report to FILE instead");
+    println!("      --ci <github|gitlab|off>  CI annotations (detected by default)");
+    println!("      --events ndjson    Stream status events as JSON lines to stdout");
+    println!("      --events-output <FILE>  Write the event stream to FILE instead");
+    println!("      --no-cache         Run every check instead of replaying cached results");
+    println!("      --badge <FILE>     Write SVG status badges for the run");
+    println!("      --watch            Re-run the checks whenever a file changes");
+    println!("      --changed [BASE]   Only check packages changed since BASE (default HEAD)");
+    println!("      --per-package      Show results per workspace package");
+    println!("      --manifest-path <PATH>  Check the package or workspace of this Cargo.toml");
+    println!("      --scan [DIR]...    Run the checks in every workspace under DIR");
+    println!("      --scan-jobs <N>    Check N workspaces at the same time (default 2)");
+    println!("      --max-processes <N>  Limit child processes (default 256, 0 disables)");
     println!("\nVerbose output can be enabled per tool:");
     println!("  -fv                    Run fmt with verbose output");
     println!("  -cv                    Run check with verbose output");
@@ -35,13 +72,17 @@ fn show_help() {
     println!("      --save-profile     Save current flags as profile");
     println!("      --use-profile      Use saved profile");
     println!("      --list-profiles    List available profiles");
+    println!("\nCommands:");
+    println!("  badge [--output <FILE>]  Write SVG badges for the last run (default status.svg)");
+    println!("  config show              Print each setting's value and source, and every check's command");
+    println!("  config validate          Report unknown keys and invalid values in the configuration");
     println!("\nFor more help: https://github.com/adamNewell/cargo-status");
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
-    let Commands::Status(args) = cli.command;
+    let Commands::Status(mut args) = cli.command;
 
     // Safety check: Prevent recursive execution
     if env::var("CARGO_STATUS_RUNNING").is_ok() {
@@ -88,6 +129,49 @@ async fn main() -> Result<()> {
         return save_profile(&args, profile_name);
     }
 
+    // Check every workspace under the scanned directories instead of this one
+    if let Some(ref dirs) = args.scan {
+        let code = scan::run(dirs, args.scan_jobs, env::args().collect()).await?;
+        if code != 0 {
+            std::process::exit(code);
+        }
+        return Ok(());
+    }
+
+    // Run from the workspace root, wherever in the workspace we were started
+    match Project::locate(args.manifest_path.as_deref()) {
+        Ok(project) => {
+            let cwd = env::current_dir().map_err(|source| cargo_status::CargoStatusError::Io {
+                context: "Failed to read the current directory".to_string(),
+                source,
+            })?;
+            args.make_paths_absolute(&cwd);
+            env::set_current_dir(&project.root).map_err(|source| cargo_status::CargoStatusError::Io {
+                context: format!("Failed to change to the workspace root {}", project.root.display()),
+                source,
+            })?;
+            // Checks run against the package we were pointed at
+            if args.manifest_path.is_some() || !project.is_root() {
+                args.manifest_path = Some(project.manifest_path);
+            }
+        }
+        Err(e) if args.manifest_path.is_some() => return Err(e),
+        Err(_) => {}
+    }
+
+    // The last run is saved under the workspace root
+    if let Some(StatusCommand::Badge(ref badge_args)) = args.action {
+        return write_badges(badge_args);
+    }
+
+    // Explain or check the configuration instead of running the checks
+    if let Some(StatusCommand::Config(ref config_args)) = args.action {
+        return match config_args.action {
+            ConfigAction::Show => show_config(args),
+            ConfigAction::Validate => validate_config(&args),
+        };
+    }
+
     // Create unified configuration
     let config = Config::new(args)?;
 
@@ -97,9 +181,28 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    // A report or event stream written to stdout replaces the terminal display
+    let report_to_stdout = config.args.format.is_some() && config.args.output.is_none();
+    let events_to_stdout = config.args.events.is_some() && config.args.events_output.is_none();
+    if report_to_stdout && events_to_stdout {
+        return Err(cargo_status::CargoStatusError::config(
+            "--format and --events cannot both write to stdout; use --output or --events-output",
+        ));
+    }
+    let stdout_taken = report_to_stdout || events_to_stdout;
+
     // Setup display system
-    let mut display = InteractiveDisplay::new();
-    let event_sender = display.event_sender();
+    let mut display = InteractiveDisplay::new().with_silent(stdout_taken);
+    let mut event_sender = display.event_sender();
+
+    // Stream events before they reach the display
+    let mut events_handle = None;
+    if config.args.events.is_some() {
+        let out = events::open_output(config.args.events_output.as_deref())?;
+        let (sender, handle) = events::tee_events(event_sender, out);
+        event_sender = sender;
+        events_handle = Some(handle);
+    }
 
     // Get enabled tool names for display initialization
     let tool_names = config.get_enabled_tools();
@@ -109,68 +212,436 @@ async fn main() -> Result<()> {
         .initialize(tool_names.clone())
         .map_err(|e| cargo_status::CargoStatusError::other(format!("Display initialization failed: {}", e)))?;
 
+    // Share one jobserver between all cargo processes
+    let jobserver = match config.jobserver_tokens() {
+        Some(tokens) => match create_jobserver(tokens) {
+            Ok(jobserver) => Some(jobserver),
+            Err(e) => {
+                eprintln!("Warning: could not create jobserver: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    // Guard against runaway process trees
+    let max_processes = config.max_processes();
+    if max_processes.is_some()
+        && let Err(e) = ProcessTable::snapshot()
+    {
+        eprintln!("Warning: process accounting unavailable, not limiting processes: {}", e);
+    }
+
+    let runner = Runner {
+        config,
+        tool_names,
+        stdout_taken,
+        jobserver,
+        max_processes,
+        event_sender,
+    };
+    if runner.config.args.watch {
+        return runner.watch(display).await;
+    }
+
     // Start the display in a background task
-    let display_handle = tokio::spawn(async move {
+    let display_handle = spawn_display(display);
+
+    let controller = RunController::new();
+    let Some(report) = runner.run(&controller).await else {
+        eprintln!("No tools available or enabled.");
+        return Ok(());
+    };
+
+    // Wait for the display to finish
+    let _ = display_handle.await;
+    runner.publish(&report)?;
+
+    // The event stream ends once its last sender is gone
+    drop(runner);
+    if let Some(handle) = events_handle {
+        let _ = handle.await;
+    }
+
+    // Exit with appropriate code
+    if report.exit_code != 0 {
+        std::process::exit(report.exit_code);
+    }
+
+    Ok(())
+}
+
+/// Runs the display until the current run completes, then hands it back
+fn spawn_display(mut display: InteractiveDisplay) -> JoinHandle<InteractiveDisplay> {
+    tokio::spawn(async move {
         if let Err(e) = display.run().await {
             eprintln!("Display error: {}", e);
         }
-    });
+        display
+    })
+}
 
-    // Create all enabled checks
-    let all_checks = create_all_checks(&config, event_sender.clone());
+/// Everything a run needs that stays the same between runs in watch mode
+struct Runner {
+    config: Config,
+    tool_names: Vec<String>,
+    /// Whether a report or event stream is written to stdout
+    stdout_taken: bool,
+    jobserver: Option<Arc<jobserver::Client>>,
+    max_processes: Option<usize>,
+    event_sender: mpsc::UnboundedSender<StatusEvent>,
+}
 
-    if all_checks.is_empty() {
-        eprintln!("No tools available or enabled.");
-        return Ok(());
+impl Runner {
+    /// Runs the enabled checks once, or returns `None` if there are none
+    async fn run(&self, controller: &RunController) -> Option<RunReport> {
+        let config = &self.config;
+        let started_at = SystemTime::now();
+        let started = Instant::now();
+
+        // Cancel in-flight checks on Ctrl-C
+        let signal_handle = controller.listen(self.event_sender.clone());
+
+        // Replay checks whose inputs haven't changed since their last run
+        let cache_dir = ToolRegistry::status_dir(config.workspace.as_ref()).join("results");
+        let result_cache = match env::current_dir()
+            .and_then(|root| ResultCache::new(cache_dir, &root, config.output_paths()))
+        {
+            Ok(cache) => Some(Arc::new(cache.with_refresh(config.args.no_cache))),
+            Err(e) => {
+                eprintln!("Warning: result cache unavailable: {}", e);
+                None
+            }
+        };
+
+        // Create all enabled checks
+        let all_checks: Vec<_> = create_all_checks(config, self.event_sender.clone())
+            .into_iter()
+            .map(|check| {
+                let verbose = check.verbose && !self.stdout_taken;
+                let check = check
+                    .with_verbose(verbose)
+                    .with_cancellation(controller.cancellation())
+                    .with_result_cache(result_cache.clone())
+                    .with_max_processes(self.max_processes);
+                match self.jobserver {
+                    Some(ref jobserver) => check.with_jobserver(jobserver.clone()),
+                    None => check,
+                }
+            })
+            .collect();
+
+        if all_checks.is_empty() {
+            signal_handle.abort();
+            return None;
+        }
+
+        // Execute checks in dependency order, in parallel unless sequential
+        let mut results = Scheduler::new(all_checks)
+            .max_parallel(config.max_parallel())
+            .sequential(config.args.sequential)
+            .run()
+            .await;
+
+        // Send completion event
+        signal_handle.abort();
+        let _ = signal_handle.await;
+        let _ = self.event_sender.send(StatusEvent::AllCompleted);
+
+        let code = if controller.is_cancelled() {
+            EXIT_INTERRUPTED
+        } else {
+            exit_code(&results)
+        };
+
+        // Lets results be tagged with the package they belong to
+        if let Some(ref workspace) = config.workspace {
+            workspace.tag_packages(&mut results);
+        }
+
+        // Reports list checks in display order
+        results.sort_by_key(|result| self.tool_names.iter().position(|name| *name == result.name));
+        Some(RunReport {
+            started_at,
+            duration: started.elapsed(),
+            results,
+            interrupted: controller.is_cancelled(),
+            exit_code: code,
+        })
     }
 
-    // Execute checks based on execution mode
-    let results = if config.args.sequential || all_checks.len() <= 1 {
-        // Run checks sequentially (fmt always first)
-        let mut results = Vec::new();
-        for check in all_checks {
-            let name = check.name.clone();
-            let result = check.run().await;
-            results.push((name, result));
+    /// Writes the report, badges and CI output of a finished run
+    fn publish(&self, report: &RunReport) -> Result<()> {
+        let config = &self.config;
+
+        if config.args.per_package && !self.stdout_taken {
+            let members: Vec<String> = config
+                .workspace
+                .iter()
+                .flat_map(|workspace| workspace.packages.iter().map(|package| package.name.clone()))
+                .collect();
+            print!("\n{}", packages::render_matrix(&report.results, &members));
         }
-        results
-    } else {
-        // Run checks in parallel
-        let mut set = JoinSet::new();
-
-        for check in all_checks {
-            let name = check.name.clone();
-            set.spawn(async move {
-                let result = check.run().await;
-                (name, result)
-            });
+
+        // Write the machine-readable report
+        if let Some(format) = config.args.format {
+            report.write(format, config.args.output.as_deref())?;
         }
 
-        let mut results = Vec::new();
-        while let Some(result) = set.join_next().await {
-            if let Ok((name, status)) = result {
-                results.push((name, status));
+        // Keep the run for `cargo status badge`
+        let last_run = last_run_path(config.workspace.as_ref());
+        if let Err(e) = report.save_last_run(&last_run) {
+            eprintln!("Warning: could not save the run to {}: {}", last_run.display(), e);
+        }
+        if let Some(ref output) = config.args.badge {
+            badge::checks_from_report(report).and_then(|checks| badge::write_badges(&checks, output))?;
+        }
+
+        // Native CI annotations, kept off stdout while a report is written there
+        match config.args.ci.unwrap_or_else(CiProvider::detect) {
+            CiProvider::Github => {
+                let written = if self.stdout_taken {
+                    github::write_workflow_commands(report, &mut std::io::stderr())
+                } else {
+                    github::write_workflow_commands(report, &mut std::io::stdout())
+                };
+                if let Err(e) = written.and_then(|_| github::append_step_summary(report)) {
+                    eprintln!("Warning: could not write GitHub Actions output: {}", e);
+                }
+            }
+            CiProvider::Gitlab => {
+                if let Err(e) = gitlab::write_code_quality(report) {
+                    eprintln!("Warning: could not write GitLab Code Quality report: {}", e);
+                }
             }
+            CiProvider::Off => {}
         }
+        Ok(())
+    }
 
-        results
-    };
+    /// Runs the checks again whenever the workspace changes, until interrupted
+    ///
+    /// Edits made while checks are running cancel them and start a new run
+    /// straight away. The display is reused, so rows stay where they are.
+    async fn watch(mut self, mut display: InteractiveDisplay) -> Result<()> {
+        let root = env::current_dir().map_err(|source| cargo_status::CargoStatusError::Io {
+            context: "Failed to read the current directory".to_string(),
+            source,
+        })?;
+        let mut watcher = Watcher::new(root, self.config.output_paths());
 
-    // Send completion event
-    let _ = event_sender.send(StatusEvent::AllCompleted);
+        loop {
+            let display_handle = spawn_display(display);
+            let controller = RunController::new();
+            let mut changed = Vec::new();
+            let report = {
+                let run = self.run(&controller);
+                tokio::pin!(run);
+                loop {
+                    tokio::select! {
+                        report = &mut run => break report,
+                        files = watcher.wait_for_change(), if changed.is_empty() => {
+                            changed = files;
+                            controller.supersede(&self.event_sender);
+                        }
+                    }
+                }
+            };
+            let Some(report) = report else {
+                eprintln!("No tools available or enabled.");
+                return Ok(());
+            };
+            display = display_handle
+                .await
+                .map_err(|e| cargo_status::CargoStatusError::other(format!("Display task failed: {}", e)))?;
 
-    // Wait for display to finish
-    let _ = display_handle.await;
+            if controller.is_cancelled() && !controller.is_superseded() {
+                std::process::exit(EXIT_INTERRUPTED);
+            }
+            if changed.is_empty() {
+                if let Err(e) = self.publish(&report) {
+                    eprintln!("Error: {}", e);
+                }
+                eprintln!("{}", "Watching for changes, press Ctrl-C to stop...".dimmed());
+                tokio::select! {
+                    files = watcher.wait_for_change() => changed = files,
+                    _ = wait_for_interrupt() => std::process::exit(report.exit_code),
+                }
+            }
 
-    // Check if all were successful
-    let all_successful = results.iter().all(|(_, status)| {
-        matches!(status, cargo_status::CheckStatus::Success { .. })
-    });
+            // Edits can change which packages --changed selects
+            if let Err(e) = self.config.resolve_changed_packages() {
+                eprintln!("Warning: keeping the previous package selection: {}", e);
+            }
+            display
+                .restart(&changed)
+                .map_err(|e| cargo_status::CargoStatusError::other(format!("Display error: {}", e)))?;
+        }
+    }
+}
 
-    // Exit with appropriate code
-    if !all_successful {
-        std::process::exit(1);
+/// Writes badges for a saved report, by default the last run
+fn write_badges(args: &BadgeArgs) -> Result<()> {
+    let report = args
+        .report
+        .clone()
+        .unwrap_or_else(|| last_run_path(Workspace::load(Path::new(".")).ok().as_ref()));
+    if !report.exists() && args.report.is_none() {
+        return Err(cargo_status::CargoStatusError::config(format!(
+            "No previous run found at {}; run `cargo status` first",
+            report.display()
+        )));
+    }
+
+    let checks = badge::read_report(&report)?;
+    for path in badge::write_badges(&checks, &args.output)? {
+        println!("Wrote {}", path.display());
     }
+    Ok(())
+}
+
+/// Prints the settings of the run `args` would start and the command line of
+/// each of its checks
+fn show_config(args: StatusArgs) -> Result<()> {
+    let config = Config {
+        dry_run: true,
+        ..Config::new(args)?
+    };
+    let (sender, _receiver) = mpsc::unbounded_channel();
+    let checks = create_all_checks(&config, sender);
 
+    println!("{}", explain::render_layers(&config.layers));
+    println!("{}", explain::render_settings(&explain::settings(&config)));
+    print!("{}", explain::render_checks(&checks));
     Ok(())
+}
+
+/// Reports the problems in the configuration, failing if there are any
+fn validate_config(args: &StatusArgs) -> Result<()> {
+    let (layers, problems) = explain::validate(args)?;
+    for layer in &layers.layers {
+        println!("Checked {} ({})", layer.origin, layer.source);
+    }
+
+    if problems.is_empty() {
+        println!("{} No problems found", "✓".green());
+        return Ok(());
+    }
+    for problem in &problems {
+        println!("{} {}", "✗".red(), problem);
+    }
+    std::process::exit(1);
+}
+
+/// Exit code used when the run was interrupted (128 + SIGINT)
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// Signal-aware controller for a single run
+///
+/// The first Ctrl-C (or SIGTERM) cancels every in-flight check, which kills
+/// its process group, and lets the display print a partial summary. A second
+/// signal restores the terminal and exits immediately.
+struct RunController {
+    cancel: watch::Sender<bool>,
+    /// Set when watch mode cancels the run because files changed
+    superseded: AtomicBool,
+}
+
+impl RunController {
+    fn new() -> Self {
+        let (cancel, _) = watch::channel(false);
+        Self {
+            cancel,
+            superseded: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns a cancellation signal for a check
+    fn cancellation(&self) -> watch::Receiver<bool> {
+        self.cancel.subscribe()
+    }
+
+    /// Whether the run has been interrupted
+    fn is_cancelled(&self) -> bool {
+        *self.cancel.borrow()
+    }
+
+    /// Whether the run was cancelled by newer changes rather than a signal
+    fn is_superseded(&self) -> bool {
+        self.superseded.load(Ordering::Relaxed)
+    }
+
+    /// Cancels the run because newer changes make its results stale
+    fn supersede(&self, event_sender: &mpsc::UnboundedSender<StatusEvent>) {
+        self.superseded.store(true, Ordering::Relaxed);
+        if !self.cancel.send_replace(true) {
+            let _ = event_sender.send(StatusEvent::Cancelled);
+        }
+    }
+
+    /// Spawns the signal listener for this run
+    fn listen(&self, event_sender: mpsc::UnboundedSender<StatusEvent>) -> JoinHandle<()> {
+        let cancel = self.cancel.clone();
+        tokio::spawn(async move {
+            if wait_for_interrupt().await.is_err() {
+                return;
+            }
+            cancel.send_replace(true);
+            let _ = event_sender.send(StatusEvent::Cancelled);
+
+            // Give up on a graceful shutdown if interrupted again
+            if wait_for_interrupt().await.is_ok() {
+                let _ = crossterm::execute!(std::io::stdout(), crossterm::cursor::Show);
+                eprintln!();
+                std::process::exit(EXIT_INTERRUPTED);
+            }
+        })
+    }
+}
+
+/// Waits for Ctrl-C, or SIGTERM on Unix
+async fn wait_for_interrupt() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await
+    }
+}
+
+/// Exit code used when checks were killed by their timeout
+const EXIT_TIMED_OUT: i32 = 124;
+
+/// Determine the process exit code from the check results
+///
+/// Failures take precedence; a run whose only problems are timeouts exits
+/// with the conventional `timeout(1)` code so hangs can be told apart.
+///
+/// Skipped checks don't count on their own: the dependency that caused the
+/// skip already determines the exit code.
+fn exit_code(results: &[CheckResult]) -> i32 {
+    let failed = results.iter().any(|result| {
+        !matches!(
+            result.status,
+            CheckStatus::Success { .. } | CheckStatus::TimedOut { .. } | CheckStatus::Skipped { .. }
+        )
+    });
+    let timed_out = results
+        .iter()
+        .any(|result| matches!(result.status, CheckStatus::TimedOut { .. }));
+
+    if failed {
+        1
+    } else if timed_out {
+        EXIT_TIMED_OUT
+    } else {
+        0
+    }
 }
\ No newline at end of file
@@ -0,0 +1,268 @@
+//! Process accounting for the runaway-process guard
+//!
+//! Before a check starts, cargo-status counts the processes it has spawned
+//! so far (cargo, rustc, build scripts, test binaries) and refuses to start
+//! more once a limit is reached. Only descendants of this cargo-status
+//! invocation are counted; cargo processes started from other terminals are
+//! ignored.
+//!
+//! On Linux the process table is read from `/proc`. Elsewhere it falls back
+//! to `ps -axo pid=,ppid=,comm=`.
+
+use std::collections::HashMap;
+use std::io;
+
+/// Default maximum number of descendant processes
+pub const DEFAULT_MAX_PROCESSES: usize = 256;
+
+/// A single entry in the process table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub name: String,
+}
+
+/// Snapshot of the processes running on the system
+#[derive(Debug, Default)]
+pub struct ProcessTable {
+    processes: HashMap<u32, ProcessInfo>,
+    children: HashMap<u32, Vec<u32>>,
+}
+
+impl ProcessTable {
+    /// Builds a table from a list of processes
+    pub fn new(processes: Vec<ProcessInfo>) -> Self {
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for process in &processes {
+            children.entry(process.ppid).or_default().push(process.pid);
+        }
+        for pids in children.values_mut() {
+            pids.sort_unstable();
+        }
+
+        Self {
+            processes: processes.into_iter().map(|p| (p.pid, p)).collect(),
+            children,
+        }
+    }
+
+    /// Reads the current process table from the operating system
+    pub fn snapshot() -> io::Result<Self> {
+        #[cfg(target_os = "linux")]
+        let processes = read_proc()?;
+        #[cfg(not(target_os = "linux"))]
+        let processes = read_ps()?;
+
+        Ok(Self::new(processes))
+    }
+
+    /// Get every process descended from `root`, not including `root` itself
+    pub fn descendants(&self, root: u32) -> Vec<&ProcessInfo> {
+        let mut found = Vec::new();
+        let mut stack = vec![root];
+        while let Some(pid) = stack.pop() {
+            for child in self.children.get(&pid).into_iter().flatten() {
+                // A pid can't be its own ancestor; guard against pid reuse races
+                if *child == root {
+                    continue;
+                }
+                if let Some(process) = self.processes.get(child) {
+                    found.push(process);
+                    stack.push(*child);
+                }
+            }
+        }
+        found
+    }
+
+    /// Renders the tree of processes below `root`
+    ///
+    /// Leaf siblings with the same name are collapsed into a single line so
+    /// that a hundred rustc processes don't drown out the rest of the tree.
+    pub fn render_tree(&self, root: u32) -> String {
+        let mut lines = Vec::new();
+        let name = self
+            .processes
+            .get(&root)
+            .map(|process| process.name.as_str())
+            .unwrap_or("cargo-status");
+        lines.push(format!("{} ({})", name, root));
+        self.render_children(root, "", &mut lines);
+        lines.join("\n")
+    }
+
+    fn render_children(&self, pid: u32, prefix: &str, lines: &mut Vec<String>) {
+        let children = self.children.get(&pid).map(Vec::as_slice).unwrap_or(&[]);
+
+        // Group leaves by name, keep subtrees as they are
+        let mut entries: Vec<(String, Option<u32>)> = Vec::new();
+        let mut leaves: Vec<(String, usize)> = Vec::new();
+        for child in children {
+            let Some(process) = self.processes.get(child) else {
+                continue;
+            };
+            if self.children.contains_key(child) {
+                entries.push((format!("{} ({})", process.name, process.pid), Some(*child)));
+            } else if let Some(leaf) = leaves.iter_mut().find(|(name, _)| *name == process.name) {
+                leaf.1 += 1;
+            } else {
+                leaves.push((process.name.clone(), 1));
+            }
+        }
+        for (name, count) in leaves {
+            let label = if count == 1 { name } else { format!("{} ×{}", name, count) };
+            entries.push((label, None));
+        }
+
+        let last = entries.len().saturating_sub(1);
+        for (index, (label, subtree)) in entries.into_iter().enumerate() {
+            let (branch, indent) = if index == last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            lines.push(format!("{}{}{}", prefix, branch, label));
+            if let Some(child) = subtree {
+                self.render_children(child, &format!("{}{}", prefix, indent), lines);
+            }
+        }
+    }
+}
+
+/// Check that this process has at most `limit` descendants
+///
+/// Returns the reason to fail the check with, including the process tree,
+/// when the limit is exceeded. Returns `None` if the process table can't be
+/// read; callers that need to know should call [`ProcessTable::snapshot`].
+pub fn check_process_limit(limit: usize) -> Option<String> {
+    let table = ProcessTable::snapshot().ok()?;
+    let root = std::process::id();
+    let count = table.descendants(root).len();
+    if count <= limit {
+        return None;
+    }
+
+    Some(format!(
+        "Too many child processes ({} running, limit {}), aborting to prevent system overload\n{}",
+        count,
+        limit,
+        table.render_tree(root)
+    ))
+}
+
+/// Read the process table from `/proc`
+#[cfg(target_os = "linux")]
+fn read_proc() -> io::Result<Vec<ProcessInfo>> {
+    let mut processes = Vec::new();
+    for entry in std::fs::read_dir("/proc")? {
+        let entry = entry?;
+        let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+            continue;
+        };
+        // Processes may exit while the directory is being read
+        let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        if let Some(process) = parse_stat(pid, &stat) {
+            processes.push(process);
+        }
+    }
+    Ok(processes)
+}
+
+/// Parse `/proc/<pid>/stat`: `pid (comm) state ppid ...`
+///
+/// `comm` may itself contain spaces and parentheses, so the fields after it
+/// are found from the last closing parenthesis.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_stat(pid: u32, stat: &str) -> Option<ProcessInfo> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?.to_string();
+    let mut fields = stat.get(close + 1..)?.split_whitespace();
+    let _state = fields.next()?;
+    let ppid = fields.next()?.parse().ok()?;
+    Some(ProcessInfo { pid, ppid, name })
+}
+
+/// Read the process table from `ps`
+#[cfg(not(target_os = "linux"))]
+fn read_ps() -> io::Result<Vec<ProcessInfo>> {
+    let output = std::process::Command::new("ps")
+        .args(["-axo", "pid=,ppid=,comm="])
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("ps exited with an error"));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().filter_map(parse_ps_line).collect())
+}
+
+/// Parse a line of `ps -axo pid=,ppid=,comm=` output
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+fn parse_ps_line(line: &str) -> Option<ProcessInfo> {
+    let mut fields = line.split_whitespace();
+    let pid = fields.next()?.parse().ok()?;
+    let ppid = fields.next()?.parse().ok()?;
+    let command = fields.collect::<Vec<_>>().join(" ");
+    let name = command.rsplit('/').next().unwrap_or(&command).to_string();
+    Some(ProcessInfo { pid, ppid, name })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, ppid: u32, name: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid,
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_process_entries() {
+        assert_eq!(
+            parse_stat(42, "42 (rustc (lib)) R 7 42 42 0 -1"),
+            Some(process(42, 7, "rustc (lib)"))
+        );
+        assert_eq!(
+            parse_ps_line("  812   1 /usr/local/bin/cargo"),
+            Some(process(812, 1, "cargo"))
+        );
+        assert_eq!(parse_ps_line("garbage"), None);
+    }
+
+    #[test]
+    fn test_descendants_and_tree() {
+        let table = ProcessTable::new(vec![
+            process(1, 0, "init"),
+            process(10, 1, "cargo-status"),
+            process(11, 10, "cargo"),
+            process(12, 11, "rustc"),
+            process(13, 11, "rustc"),
+            process(14, 11, "build-script-build"),
+            process(15, 10, "cargo"),
+            process(16, 15, "test-binary"),
+            process(20, 1, "cargo"),
+            process(21, 20, "rustc"),
+        ]);
+
+        let mut pids: Vec<u32> = table.descendants(10).iter().map(|p| p.pid).collect();
+        pids.sort_unstable();
+        assert_eq!(pids, vec![11, 12, 13, 14, 15, 16]);
+
+        assert_eq!(
+            table.render_tree(10),
+            "cargo-status (10)\n\
+             ├── cargo (11)\n\
+             │   ├── rustc ×2\n\
+             │   └── build-script-build\n\
+             └── cargo (15)\n    \
+                 └── test-binary"
+        );
+    }
+}
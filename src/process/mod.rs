@@ -0,0 +1,53 @@
+//! Child process management for cargo-status
+//!
+//! Each check runs in its own process group so that the whole tree it
+//! spawns (cargo, rustc, build scripts, test binaries) can be terminated
+//! together when the check times out.
+//!
+//! Checks running in parallel also share a single GNU make jobserver, passed
+//! to every cargo process through `CARGO_MAKEFLAGS`, so that the total number
+//! of rustc jobs stays bounded no matter how many checks are running.
+
+pub mod accounting;
+
+use std::io;
+use std::sync::Arc;
+use tokio::process::{Child, Command};
+
+/// Creates a jobserver holding `tokens` job slots to share between checks
+///
+/// Each cargo process also holds one implicit slot of its own, so the total
+/// number of concurrent jobs is at most `tokens` plus the number of running checks.
+pub fn create_jobserver(tokens: usize) -> io::Result<Arc<jobserver::Client>> {
+    jobserver::Client::new(tokens).map(Arc::new)
+}
+
+/// Makes the jobserver available to the command through `CARGO_MAKEFLAGS`
+pub fn configure_jobserver(cmd: &mut Command, jobserver: &jobserver::Client) {
+    jobserver.configure(cmd.as_std_mut());
+}
+
+/// Places the command in a new process group once spawned
+pub fn configure_process_group(cmd: &mut Command) {
+    #[cfg(unix)]
+    cmd.process_group(0);
+    #[cfg(not(unix))]
+    let _ = cmd;
+}
+
+/// Kills the child and every process in its process group
+///
+/// Falls back to killing only the direct child where process groups
+/// are not available.
+pub fn kill_process_group(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: kill(2) with a negative pid signals the process group we created
+        // for this child; it has no memory-safety preconditions.
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+        }
+    }
+
+    let _ = child.start_kill();
+}
@@ -0,0 +1,333 @@
+//! Shields-style SVG status badges
+//!
+//! Badges summarise a run for a README without an external service: one for
+//! the overall status, one for the test counts, and one per check. They are
+//! built from a JSON report, by default the one every run saves to
+//! `target/cargo-status/last-run.json`.
+
+use crate::error::{CargoStatusError, Result};
+use crate::report::{json, RunReport};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const GREEN: &str = "#4c1";
+const YELLOW: &str = "#dfb317";
+const RED: &str = "#e05d44";
+const GREY: &str = "#9f9f9f";
+
+/// A single badge, e.g. `clippy: 14 warnings`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Badge {
+    pub label: String,
+    pub message: String,
+    pub color: &'static str,
+}
+
+/// Check as recorded in a JSON report
+#[derive(Debug, Clone, Deserialize)]
+pub struct BadgeCheck {
+    pub name: String,
+    pub status: String,
+    pub counts: BadgeCounts,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct BadgeCounts {
+    pub errors: usize,
+    pub warnings: usize,
+    pub tests_passed: Option<usize>,
+    pub tests_failed: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct ReportChecks {
+    checks: Vec<BadgeCheck>,
+}
+
+/// Reads the checks of a JSON report written with `--format json`
+pub fn read_report(path: &Path) -> Result<Vec<BadgeCheck>> {
+    let text = fs::read_to_string(path).map_err(|source| CargoStatusError::Io {
+        context: format!("Failed to read report {}", path.display()),
+        source,
+    })?;
+    checks_from_json(&text)
+        .map_err(|e| CargoStatusError::other(format!("Invalid report {}: {}", path.display(), e)))
+}
+
+/// Checks of a finished run, as they would be read back from its JSON report
+pub fn checks_from_report(report: &RunReport) -> Result<Vec<BadgeCheck>> {
+    let text = json::render(report)?;
+    checks_from_json(&text)
+        .map_err(|e| CargoStatusError::other(format!("Failed to summarise report: {}", e)))
+}
+
+fn checks_from_json(text: &str) -> serde_json::Result<Vec<BadgeCheck>> {
+    serde_json::from_str::<ReportChecks>(text).map(|report| report.checks)
+}
+
+/// Badge for the run as a whole
+pub fn summary_badge(checks: &[BadgeCheck]) -> Badge {
+    let errors: usize = checks.iter().map(|check| check.counts.errors).sum();
+    let warnings: usize = checks.iter().map(|check| check.counts.warnings).sum();
+    let any = |status: &str| checks.iter().any(|check| check.status == status);
+
+    let (message, color) = if any("failed") || any("timed_out") {
+        ("failed".to_string(), RED)
+    } else if errors > 0 {
+        (plural(errors, "error"), RED)
+    } else if warnings > 0 {
+        (plural(warnings, "warning"), YELLOW)
+    } else if any("cancelled") {
+        ("cancelled".to_string(), GREY)
+    } else {
+        ("passing".to_string(), GREEN)
+    };
+    badge("status", message, color)
+}
+
+/// Badge with the test counts of the run, if any check ran tests
+pub fn tests_badge(checks: &[BadgeCheck]) -> Option<Badge> {
+    let counted: Vec<_> = checks
+        .iter()
+        .filter(|check| check.counts.tests_passed.is_some())
+        .collect();
+    if counted.is_empty() {
+        return None;
+    }
+    let passed: usize = counted.iter().filter_map(|check| check.counts.tests_passed).sum();
+    let failed: usize = counted.iter().filter_map(|check| check.counts.tests_failed).sum();
+
+    Some(if failed > 0 {
+        badge("tests", format!("{} failed, {} passed", failed, passed), RED)
+    } else {
+        badge("tests", format!("{} passed", passed), GREEN)
+    })
+}
+
+/// Badge for a single check, labelled with its lowercase name
+pub fn check_badge(check: &BadgeCheck) -> Badge {
+    let (message, color) = match check.status.as_str() {
+        "success" => ("passing".to_string(), GREEN),
+        "warning" => (plural(check.counts.warnings, "warning"), YELLOW),
+        "error" => (plural(check.counts.errors, "error"), RED),
+        "failed" => ("failed".to_string(), RED),
+        "timed_out" => ("timed out".to_string(), RED),
+        other => (other.to_string(), GREY),
+    };
+    badge(&check.name.to_lowercase(), message, color)
+}
+
+/// Writes the summary badge to `output` and the other badges next to it
+///
+/// For `status.svg` these are `status-tests.svg` and `status-<check>.svg`.
+/// Returns the paths that were written.
+pub fn write_badges(checks: &[BadgeCheck], output: &Path) -> Result<Vec<PathBuf>> {
+    let mut badges = vec![(output.to_path_buf(), summary_badge(checks))];
+    if let Some(tests) = tests_badge(checks) {
+        badges.push((sibling(output, "tests"), tests));
+    }
+    for check in checks {
+        badges.push((sibling(output, &check.name.to_lowercase()), check_badge(check)));
+    }
+
+    if let Some(parent) = output.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|source| CargoStatusError::Io {
+            context: format!("Failed to create {}", parent.display()),
+            source,
+        })?;
+    }
+    for (path, badge) in &badges {
+        fs::write(path, render_svg(badge)).map_err(|source| CargoStatusError::Io {
+            context: format!("Failed to write badge {}", path.display()),
+            source,
+        })?;
+    }
+    Ok(badges.into_iter().map(|(path, _)| path).collect())
+}
+
+/// Every file [`write_badges`] may write for a run of the named checks
+pub fn badge_paths(output: &Path, check_names: &[String]) -> Vec<PathBuf> {
+    let mut paths = vec![output.to_path_buf(), sibling(output, "tests")];
+    paths.extend(check_names.iter().map(|name| sibling(output, &name.to_lowercase())));
+    paths
+}
+
+/// Path of the badge with the given suffix next to the summary badge
+fn sibling(output: &Path, suffix: &str) -> PathBuf {
+    let stem = output
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "status".to_string());
+    output.with_file_name(format!("{}-{}.svg", stem, suffix))
+}
+
+/// Renders a badge in the shields.io "flat" style
+pub fn render_svg(badge: &Badge) -> String {
+    let label_width = text_width(&badge.label) + 10;
+    let message_width = text_width(&badge.message) + 10;
+    let width = label_width + message_width;
+    let label = escape(&badge.label);
+    let message = escape(&badge.message);
+    // Text is drawn at 10x scale for sub-pixel positioning
+    let label_x = label_width * 5;
+    let message_x = (label_width * 2 + message_width) * 5;
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}">
+<title>{label}: {message}</title>
+<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>
+<clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>
+<g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="#555"/><rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/><rect width="{width}" height="20" fill="url(#s)"/></g>
+<g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="110">
+<text x="{label_x}" y="150" fill="#010101" fill-opacity=".3" transform="scale(.1)">{label}</text><text x="{label_x}" y="140" transform="scale(.1)">{label}</text>
+<text x="{message_x}" y="150" fill="#010101" fill-opacity=".3" transform="scale(.1)">{message}</text><text x="{message_x}" y="140" transform="scale(.1)">{message}</text>
+</g>
+</svg>
+"##,
+        color = badge.color,
+    )
+}
+
+/// Approximate width in pixels of text in 11px Verdana
+fn text_width(text: &str) -> usize {
+    let tenths: usize = text
+        .chars()
+        .map(|c| match c {
+            'i' | 'j' | 'l' | '.' | ',' | ':' | ';' | '\'' | '|' | '!' => 35,
+            ' ' | 'f' | 'r' | 't' | '(' | ')' | '[' | ']' | '-' | 'I' => 45,
+            'm' | 'w' | 'M' | 'W' => 100,
+            'A'..='Z' => 75,
+            _ => 68,
+        })
+        .sum();
+    tenths.div_ceil(10)
+}
+
+fn badge(label: &str, message: String, color: &'static str) -> Badge {
+    Badge {
+        label: label.to_string(),
+        message,
+        color,
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::CheckStatus;
+    use crate::tools::test_results::read_nextest_junit;
+    use crate::tools::CheckResult;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn check(name: &str, status: &str, counts: BadgeCounts) -> BadgeCheck {
+        BadgeCheck {
+            name: name.to_string(),
+            status: status.to_string(),
+            counts,
+        }
+    }
+
+    #[test]
+    fn test_badges_from_checks() {
+        let checks = vec![
+            check("Format", "success", BadgeCounts::default()),
+            check(
+                "Clippy",
+                "warning",
+                BadgeCounts {
+                    warnings: 3,
+                    ..BadgeCounts::default()
+                },
+            ),
+            check(
+                "Test",
+                "success",
+                BadgeCounts {
+                    tests_passed: Some(412),
+                    tests_failed: Some(0),
+                    ..BadgeCounts::default()
+                },
+            ),
+        ];
+
+        assert_eq!(summary_badge(&checks), badge("status", "3 warnings".to_string(), YELLOW));
+        assert_eq!(tests_badge(&checks), Some(badge("tests", "412 passed".to_string(), GREEN)));
+        assert_eq!(check_badge(&checks[1]).label, "clippy");
+        assert_eq!(tests_badge(&checks[..2]), None);
+
+        let svg = render_svg(&summary_badge(&checks));
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.contains("aria-label=\"status: 3 warnings\""));
+        assert!(svg.contains("fill=\"#dfb317\""));
+        assert!(roxmltree::Document::parse(&svg).is_ok());
+    }
+
+    #[test]
+    fn test_tests_badge_from_nextest_results() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let junit_path = dir.path().join("junit.xml");
+        fs::write(
+            &junit_path,
+            r#"<testsuites>
+                <testsuite name="demo"><testcase name="adds"/><testcase name="parses"/></testsuite>
+                <testsuite name="demo::api"><testcase name="creates"><failure message="boom"/></testcase></testsuite>
+            </testsuites>"#,
+        )
+        .unwrap();
+
+        let duration = Duration::from_secs(3);
+        let test = CheckResult {
+            test_cases: read_nextest_junit(&junit_path).unwrap(),
+            ..CheckResult::for_test(
+                "Test",
+                CheckStatus::Error {
+                    errors: 1,
+                    warnings: 0,
+                    duration,
+                },
+            )
+        };
+        let report = RunReport {
+            started_at: UNIX_EPOCH,
+            duration,
+            results: vec![test],
+            interrupted: false,
+            exit_code: 1,
+        };
+
+        let checks = checks_from_report(&report).unwrap();
+        assert_eq!(tests_badge(&checks), Some(badge("tests", "1 failed, 2 passed".to_string(), RED)));
+    }
+
+    #[test]
+    fn test_write_badges() {
+        let dir = std::env::temp_dir().join(format!("cargo-status-badges-{}", std::process::id()));
+        let checks = vec![check(
+            "Build",
+            "error",
+            BadgeCounts {
+                errors: 2,
+                ..BadgeCounts::default()
+            },
+        )];
+
+        let written = write_badges(&checks, &dir.join("status.svg")).unwrap();
+        assert_eq!(written, vec![dir.join("status.svg"), dir.join("status-build.svg")]);
+        let build = fs::read_to_string(dir.join("status-build.svg")).unwrap();
+        assert!(build.contains("build: 2 errors"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,29 @@
+//! Detection of the CI system cargo-status runs under
+
+use serde::{Deserialize, Serialize};
+use std::env;
+
+/// CI system whose native annotations cargo-status produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum CiProvider {
+    /// Workflow commands, log groups and a job summary on GitHub Actions
+    Github,
+    /// A Code Quality report on GitLab CI
+    Gitlab,
+    /// No CI-specific output
+    Off,
+}
+
+impl CiProvider {
+    /// Detects the CI system from the environment variables it sets
+    pub fn detect() -> Self {
+        if env::var("GITHUB_ACTIONS").is_ok_and(|value| value == "true") {
+            Self::Github
+        } else if env::var("GITLAB_CI").is_ok_and(|value| value == "true") {
+            Self::Gitlab
+        } else {
+            Self::Off
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! GitHub Actions reporter
+//!
+//! Prints each check's output in a collapsible log group and turns
+//! diagnostics into `::error` / `::warning` workflow commands, which GitHub
+//! shows as annotations on the changed lines of a pull request. The
+//! Markdown report is appended to the job summary.
+
+use crate::display::{format_duration, CheckStatus};
+use crate::report::{markdown, status_summary, unique_diagnostics, RunReport};
+use crate::tools::{CheckResult, Diagnostic, TestOutcome};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Writes log groups and annotations for every check
+pub fn write_workflow_commands(report: &RunReport, out: &mut impl Write) -> io::Result<()> {
+    // Tool output must not be able to issue workflow commands of its own
+    let token = format!(
+        "cargo-status-{:x}-{:x}",
+        std::process::id(),
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+    );
+
+    for result in &report.results {
+        writeln!(
+            out,
+            "::group::{} ({}, {})",
+            escape_data(&result.name),
+            escape_data(&status_summary(&result.status)),
+            format_duration(result.duration)
+        )?;
+        writeln!(out, "::stop-commands::{}", token)?;
+        for line in &result.output {
+            writeln!(out, "{}", line)?;
+        }
+        writeln!(out, "::{}::", token)?;
+        writeln!(out, "::endgroup::")?;
+    }
+
+    // A warning several checks report is annotated once
+    for (check, diagnostic) in unique_diagnostics(report) {
+        writeln!(out, "{}", diagnostic_annotation(check, diagnostic))?;
+    }
+    for result in &report.results {
+        for annotation in annotations(result) {
+            writeln!(out, "{}", annotation)?;
+        }
+    }
+    Ok(())
+}
+
+/// Workflow commands for failed tests and for problems with the check itself
+fn annotations(result: &CheckResult) -> Vec<String> {
+    let mut annotations = Vec::new();
+    for case in &result.test_cases {
+        if case.outcome == TestOutcome::Failed {
+            annotations.push(format!(
+                "::error title={}::{}",
+                escape_property(&format!("{}: {} failed", result.name, case.name)),
+                escape_data(case.message.as_deref().unwrap_or("test failed"))
+            ));
+        }
+    }
+
+    // Problems with the check itself have no location to attach to
+    match result.status {
+        CheckStatus::Failed { ref reason, .. } => annotations.push(format!(
+            "::error title={}::{}",
+            escape_property(&format!("{} failed", result.name)),
+            escape_data(reason)
+        )),
+        CheckStatus::TimedOut { .. } => annotations.push(format!(
+            "::error title={}::{}",
+            escape_property(&format!("{} timed out", result.name)),
+            escape_data(&status_summary(&result.status))
+        )),
+        _ => {}
+    }
+    annotations
+}
+
+fn diagnostic_annotation(check: &str, diagnostic: &Diagnostic) -> String {
+    let mut properties = Vec::new();
+    if let Some(ref file) = diagnostic.file {
+        properties.push(format!("file={}", escape_property(file)));
+        if let Some(line) = diagnostic.line {
+            properties.push(format!("line={}", line));
+        }
+        if let Some(column) = diagnostic.column {
+            properties.push(format!("col={}", column));
+        }
+        if let Some(end_line) = diagnostic.end_line {
+            properties.push(format!("endLine={}", end_line));
+        }
+        if let Some(end_column) = diagnostic.end_column {
+            properties.push(format!("endColumn={}", end_column));
+        }
+    }
+    let title = match diagnostic.code {
+        Some(ref code) => format!("{}: {}", check, code),
+        None => check.to_string(),
+    };
+    properties.push(format!("title={}", escape_property(&title)));
+
+    format!(
+        "::{} {}::{}",
+        diagnostic.level.as_str(),
+        properties.join(","),
+        escape_data(&diagnostic.message)
+    )
+}
+
+/// Appends the Markdown summary to the file named by `$GITHUB_STEP_SUMMARY`
+pub fn append_step_summary(report: &RunReport) -> io::Result<()> {
+    let Some(path) = std::env::var_os("GITHUB_STEP_SUMMARY") else {
+        return Ok(());
+    };
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", markdown::render(report))
+}
+
+/// Escapes the message of a workflow command
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a property value of a workflow command
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::DiagnosticLevel;
+    use std::time::Duration;
+
+    #[test]
+    fn test_workflow_commands() {
+        let mut diagnostic =
+            Diagnostic::new(DiagnosticLevel::Warning, "unused variable: `x`\n100% sure");
+        diagnostic.code = Some("unused_variables".to_string());
+        diagnostic.file = Some("src/lib.rs".to_string());
+        diagnostic.line = Some(3);
+        diagnostic.column = Some(9);
+
+        let duration = Duration::from_millis(250);
+        let report = RunReport {
+            started_at: UNIX_EPOCH,
+            duration,
+            results: vec![CheckResult {
+                output: vec!["::error::injected".to_string()],
+                ..CheckResult::for_test("Check", CheckStatus::Warning { warnings: 1, duration })
+                    .with_diagnostics(vec![diagnostic])
+            }],
+            interrupted: false,
+            exit_code: 1,
+        };
+
+        let mut out = Vec::new();
+        write_workflow_commands(&report, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert_eq!(lines[0], "::group::Check (1 warning, 250ms)");
+        assert!(lines[1].starts_with("::stop-commands::"));
+        assert_eq!(lines[2], "::error::injected");
+        assert_eq!(lines[4], "::endgroup::");
+        assert_eq!(
+            lines[5],
+            "::warning file=src/lib.rs,line=3,col=9,title=Check%3A unused_variables::unused variable: `x`%0A100%25 sure"
+        );
+
+        let summary = markdown::render(&report);
+        assert!(summary.contains("| Check | ⚠️ 1 warning | 0 | 1 | 250ms |"));
+    }
+
+    #[test]
+    fn test_annotation_reported_by_several_checks() {
+        let mut diagnostic = Diagnostic::new(DiagnosticLevel::Warning, "unused variable: `x`");
+        diagnostic.file = Some("src/lib.rs".to_string());
+        diagnostic.line = Some(3);
+
+        let duration = Duration::from_secs(1);
+        let status = CheckStatus::Warning { warnings: 1, duration };
+        let report = RunReport {
+            started_at: UNIX_EPOCH,
+            duration,
+            results: ["Check", "Clippy", "Build"]
+                .into_iter()
+                .map(|name| {
+                    CheckResult::for_test(name, status.clone()).with_diagnostics(vec![diagnostic.clone()])
+                })
+                .collect(),
+            interrupted: false,
+            exit_code: 0,
+        };
+
+        let mut out = Vec::new();
+        write_workflow_commands(&report, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let annotations: Vec<&str> = out.lines().filter(|line| line.starts_with("::warning ")).collect();
+        assert_eq!(annotations, ["::warning file=src/lib.rs,line=3,title=Check::unused variable: `x`"]);
+    }
+}
@@ -0,0 +1,183 @@
+//! GitLab Code Quality report
+//!
+//! GitLab reads a JSON array of issues from the `codequality` report
+//! artifact and shows new and fixed issues on merge requests. Issues are
+//! matched between pipelines by their fingerprint.
+
+use crate::error::{CargoStatusError, Result};
+use crate::report::{unique_diagnostics, RunReport};
+use crate::tools::{Diagnostic, DiagnosticLevel};
+use serde::Serialize;
+
+/// File name GitLab's documentation uses for the Code Quality artifact
+pub const CODE_QUALITY_FILE: &str = "gl-code-quality-report.json";
+
+#[derive(Serialize)]
+struct Issue<'a> {
+    description: &'a str,
+    check_name: String,
+    fingerprint: String,
+    severity: &'static str,
+    location: Location<'a>,
+}
+
+#[derive(Serialize)]
+struct Location<'a> {
+    path: &'a str,
+    lines: Lines,
+}
+
+#[derive(Serialize)]
+struct Lines {
+    begin: usize,
+}
+
+/// Renders every located diagnostic as a Code Quality issue
+///
+/// Diagnostics without a file can't be shown on a merge request and are left
+/// out, as are duplicates of a diagnostic an earlier check already reported.
+pub fn render(report: &RunReport) -> Result<String> {
+    let issues: Vec<Issue> = unique_diagnostics(report)
+        .into_iter()
+        .filter_map(|(check, diagnostic)| issue(check, diagnostic))
+        .collect();
+
+    serde_json::to_string_pretty(&issues).map_err(|e| {
+        CargoStatusError::other(format!("Failed to serialize Code Quality report: {}", e))
+    })
+}
+
+/// Writes the Code Quality report to [`CODE_QUALITY_FILE`] in the current directory
+pub fn write_code_quality(report: &RunReport) -> Result<()> {
+    let rendered = render(report)?;
+    std::fs::write(CODE_QUALITY_FILE, rendered).map_err(|source| CargoStatusError::Io {
+        context: format!("Failed to write {}", CODE_QUALITY_FILE),
+        source,
+    })
+}
+
+fn issue<'a>(check: &str, diagnostic: &'a Diagnostic) -> Option<Issue<'a>> {
+    let path = diagnostic.file.as_deref()?;
+    let line = diagnostic.line.unwrap_or(1);
+    let check_name = match diagnostic.code {
+        Some(ref code) => code.clone(),
+        None => format!("{}/{}", check.to_lowercase(), diagnostic.level.as_str()),
+    };
+    // Which check reported the problem doesn't change its identity, so
+    // enabling or disabling a check doesn't turn it into a new issue
+    let rule = diagnostic.code.as_deref().unwrap_or(diagnostic.level.as_str());
+    let fingerprint = format!(
+        "{:016x}",
+        fnv1a(&[rule, path, &line.to_string(), &diagnostic.message])
+    );
+
+    Some(Issue {
+        description: &diagnostic.message,
+        check_name,
+        fingerprint,
+        severity: match diagnostic.level {
+            DiagnosticLevel::Error => "major",
+            DiagnosticLevel::Warning => "minor",
+        },
+        location: Location {
+            path,
+            lines: Lines { begin: line },
+        },
+    })
+}
+
+/// 64-bit FNV-1a hash of the given fields, separated by NUL bytes
+fn fnv1a(fields: &[&str]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let mut hash = OFFSET_BASIS;
+    for (index, field) in fields.iter().enumerate() {
+        let separator: &[u8] = if index == 0 { &[] } else { &[0] };
+        for byte in separator.iter().chain(field.as_bytes()) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::CheckStatus;
+    use crate::tools::CheckResult;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_fnv1a() {
+        // Reference values for 64-bit FNV-1a
+        assert_eq!(fnv1a(&[""]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(&["a"]), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_render_code_quality_report() {
+        let mut located = Diagnostic::new(DiagnosticLevel::Error, "mismatched types");
+        located.code = Some("E0308".to_string());
+        located.file = Some("src/main.rs".to_string());
+        located.line = Some(12);
+        let unlocated = Diagnostic::new(DiagnosticLevel::Warning, "unused manifest key");
+
+        let duration = Duration::from_secs(1);
+        let report = RunReport {
+            started_at: UNIX_EPOCH,
+            duration,
+            results: vec![CheckResult::for_test(
+                "Build",
+                CheckStatus::Error {
+                    errors: 1,
+                    warnings: 1,
+                    duration,
+                },
+            )
+            .with_diagnostics(vec![located, unlocated])],
+            interrupted: false,
+            exit_code: 1,
+        };
+
+        let issues: serde_json::Value = serde_json::from_str(&render(&report).unwrap()).unwrap();
+        let issues = issues.as_array().unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0]["check_name"], "E0308");
+        assert_eq!(issues[0]["severity"], "major");
+        assert_eq!(issues[0]["location"]["lines"]["begin"], 12);
+        assert_eq!(issues[0]["fingerprint"].as_str().unwrap().len(), 16);
+    }
+
+    #[test]
+    fn test_issue_reported_by_several_checks() {
+        let mut warning = Diagnostic::new(DiagnosticLevel::Warning, "unused variable: `x`");
+        warning.file = Some("src/lib.rs".to_string());
+        warning.line = Some(3);
+
+        let duration = Duration::from_secs(1);
+        let status = CheckStatus::Warning { warnings: 1, duration };
+        let report = |checks: &[&str]| RunReport {
+            started_at: UNIX_EPOCH,
+            duration,
+            results: checks
+                .iter()
+                .map(|name| {
+                    CheckResult::for_test(name, status.clone()).with_diagnostics(vec![warning.clone()])
+                })
+                .collect(),
+            interrupted: false,
+            exit_code: 0,
+        };
+        let issues = |checks: &[&str]| -> Vec<serde_json::Value> {
+            let rendered = render(&report(checks)).unwrap();
+            serde_json::from_str(&rendered).unwrap()
+        };
+
+        let all = issues(&["Check", "Clippy", "Build"]);
+        assert_eq!(all.len(), 1);
+        let clippy_only = issues(&["Clippy"]);
+        assert_eq!(all[0]["fingerprint"], clippy_only[0]["fingerprint"]);
+    }
+}
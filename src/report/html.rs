@@ -0,0 +1,493 @@
+//! Self-contained HTML report for nightly runs
+//!
+//! A single static page with a summary table, a timing bar per check, and one
+//! section per check holding its diagnostics grouped by file and its output,
+//! with ANSI colors turned into styled HTML. Styles are inlined so the file
+//! can be archived or attached without anything else.
+
+use crate::display::{format_duration, CheckStatus};
+use crate::report::packages::{cell, package_counts, package_names};
+use crate::report::{rfc3339, status_counts, status_name, status_summary, RunReport};
+use crate::tools::{CheckResult, Diagnostic, TestOutcome};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const STYLE: &str = "\
+body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; margin: 2em auto; max-width: 72em; padding: 0 1em; color: #1f2328; }
+h1 { margin-bottom: 0.2em; }
+.meta { color: #59636e; margin-top: 0; }
+table { border-collapse: collapse; width: 100%; margin: 1em 0; }
+th, td { border-bottom: 1px solid #d1d9e0; padding: 0.4em 0.6em; text-align: left; }
+td.number { text-align: right; }
+.bar { background: #0969da; height: 0.8em; border-radius: 2px; min-width: 1px; }
+.status { font-weight: bold; }
+.success { color: #1a7f37; }
+.warning { color: #9a6700; }
+.error, .failed, .timed_out { color: #d1242f; }
+.cancelled, .skipped, .pending, .running { color: #59636e; }
+section { border: 1px solid #d1d9e0; border-radius: 6px; margin: 1.5em 0; padding: 0 1em 1em; }
+code { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; }
+pre { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; background: #0d1117; color: #e6edf3; padding: 1em; overflow-x: auto; border-radius: 6px; font-size: 0.85em; }
+ul.diagnostics { list-style: none; padding-left: 1em; }
+";
+
+/// Renders the run as a standalone HTML page
+pub fn render(report: &RunReport) -> String {
+    let passed = report
+        .results
+        .iter()
+        .all(|result| matches!(result.status, CheckStatus::Success { .. }));
+    let longest = report
+        .results
+        .iter()
+        .map(|result| result.duration)
+        .max()
+        .unwrap_or_default()
+        .as_secs_f64();
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>cargo status report</title>\n<style>\n{}</style>\n</head>\n<body>\n",
+        STYLE
+    );
+
+    let _ = writeln!(
+        html,
+        "<h1>cargo status: <span class=\"{}\">{}</span></h1>",
+        if passed { "success" } else { "error" },
+        if passed { "passed" } else { "failed" }
+    );
+    let _ = writeln!(
+        html,
+        "<p class=\"meta\">Started {} &middot; {} {} &middot; exit code {}</p>",
+        rfc3339(report.started_at),
+        if report.interrupted { "cancelled after" } else { "completed in" },
+        format_duration(report.duration),
+        report.exit_code
+    );
+
+    html.push_str("<table>\n<tr><th>Check</th><th>Status</th><th>Errors</th><th>Warnings</th><th>Duration</th><th></th></tr>\n");
+    for (index, result) in report.results.iter().enumerate() {
+        let (errors, warnings) = status_counts(&result.status);
+        let width = if longest > 0.0 {
+            result.duration.as_secs_f64() / longest * 100.0
+        } else {
+            0.0
+        };
+        let _ = writeln!(
+            html,
+            "<tr><td><a href=\"#check-{}\">{}</a></td><td class=\"status {}\">{}</td>\
+             <td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td>\
+             <td style=\"width: 30%\"><div class=\"bar\" style=\"width: {:.1}%\"></div></td></tr>",
+            index,
+            escape(&result.name),
+            status_name(&result.status),
+            escape(&status_summary(&result.status)),
+            errors,
+            warnings,
+            format_duration(result.duration),
+            width
+        );
+    }
+    html.push_str("</table>\n");
+    package_table(&mut html, &report.results);
+
+    for (index, result) in report.results.iter().enumerate() {
+        check_section(&mut html, index, result);
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Crate-by-check table, when the checks reported on several packages
+fn package_table(html: &mut String, results: &[CheckResult]) {
+    let packages = package_names(results);
+    if packages.len() < 2 {
+        return;
+    }
+    let counts: Vec<_> = results.iter().map(package_counts).collect();
+    html.push_str("<h2>Packages</h2>\n<table>\n<tr><th>Package</th>");
+    for result in results {
+        let _ = write!(html, "<th>{}</th>", escape(&result.name));
+    }
+    html.push_str("</tr>\n");
+    for package in &packages {
+        let _ = write!(html, "<tr><td>{}</td>", escape(package));
+        for (result, counts) in results.iter().zip(&counts) {
+            let class = match counts.get(package) {
+                Some(counts) if counts.errors > 0 || counts.tests_failed > 0 => "error",
+                Some(counts) if counts.warnings > 0 => "warning",
+                _ => "success",
+            };
+            let _ = write!(
+                html,
+                "<td class=\"{}\">{}</td>",
+                class,
+                escape(&cell(result, counts.get(package)))
+            );
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+}
+
+fn check_section(html: &mut String, index: usize, result: &CheckResult) {
+    let _ = writeln!(
+        html,
+        "<section id=\"check-{}\">\n<h2>{} <span class=\"status {}\">{}</span></h2>",
+        index,
+        escape(&result.name),
+        status_name(&result.status),
+        escape(&status_summary(&result.status))
+    );
+
+    let mut meta = format!("Duration {}", format_duration(result.duration));
+    if let Some(code) = result.exit_code {
+        let _ = write!(meta, " &middot; exit code {}", code);
+    }
+    if let Some(tests) = result.test_counts() {
+        let _ = write!(meta, " &middot; {} passed, {} failed", tests.passed, tests.failed);
+    }
+    if let Some(ref version) = result.tool_version {
+        let _ = write!(meta, " &middot; {}", escape(version));
+    }
+    let _ = writeln!(html, "<p class=\"meta\">{}</p>", meta);
+    let _ = writeln!(html, "<p><code>{}</code></p>", escape(&result.command.join(" ")));
+
+    // Failure reasons may span several lines (e.g. a process tree)
+    if let CheckStatus::Failed { ref reason, .. } = result.status {
+        let _ = writeln!(html, "<pre>{}</pre>", escape(reason));
+    }
+
+    let failed_tests: Vec<_> = result
+        .test_cases
+        .iter()
+        .filter(|case| case.outcome == TestOutcome::Failed)
+        .collect();
+    if !failed_tests.is_empty() {
+        html.push_str("<h3>Failed tests</h3>\n<ul>\n");
+        for case in failed_tests {
+            let _ = writeln!(html, "<li><code>{}</code></li>", escape(&case.name));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    if !result.diagnostics.is_empty() {
+        html.push_str("<h3>Diagnostics</h3>\n");
+        for (file, diagnostics) in group_by_file(&result.diagnostics) {
+            let _ = writeln!(
+                html,
+                "<h4><code>{}</code></h4>\n<ul class=\"diagnostics\">",
+                escape(file.unwrap_or("(no location)"))
+            );
+            for diagnostic in diagnostics {
+                let _ = writeln!(html, "<li>{}</li>", diagnostic_item(diagnostic));
+            }
+            html.push_str("</ul>\n");
+        }
+    }
+
+    if !result.output.is_empty() {
+        let _ = writeln!(
+            html,
+            "<h3>Output</h3>\n<pre>{}</pre>",
+            ansi_to_html(&result.output.join("\n"))
+        );
+    }
+    html.push_str("</section>\n");
+}
+
+/// Diagnostics by file, with diagnostics without a location last
+fn group_by_file(diagnostics: &[Diagnostic]) -> Vec<(Option<&str>, Vec<&Diagnostic>)> {
+    let mut by_file: BTreeMap<&str, Vec<&Diagnostic>> = BTreeMap::new();
+    let mut unlocated = Vec::new();
+    for diagnostic in diagnostics {
+        match diagnostic.file {
+            Some(ref file) => by_file.entry(file).or_default().push(diagnostic),
+            None => unlocated.push(diagnostic),
+        }
+    }
+
+    let mut groups: Vec<_> = by_file
+        .into_iter()
+        .map(|(file, mut diagnostics)| {
+            diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+            (Some(file), diagnostics)
+        })
+        .collect();
+    if !unlocated.is_empty() {
+        groups.push((None, unlocated));
+    }
+    groups
+}
+
+fn diagnostic_item(diagnostic: &Diagnostic) -> String {
+    let level = diagnostic.level.as_str();
+    let mut item = format!("<span class=\"status {}\">{}</span>", level, level);
+    if let Some(line) = diagnostic.line {
+        match diagnostic.column {
+            Some(column) => {
+                let _ = write!(item, " <code>{}:{}</code>", line, column);
+            }
+            None => {
+                let _ = write!(item, " <code>{}</code>", line);
+            }
+        }
+    }
+    let _ = write!(item, " {}", escape(&diagnostic.message));
+    if let Some(ref code) = diagnostic.code {
+        let _ = write!(item, " (<code>{}</code>)", escape(code));
+    }
+    item
+}
+
+/// Converts text with ANSI SGR color codes into HTML with inline styles
+///
+/// Other escape sequences and control characters are dropped.
+pub fn ansi_to_html(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    let mut style = Style::default();
+    let mut span_open = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' => {
+                if chars.peek() != Some(&'[') {
+                    continue;
+                }
+                chars.next();
+                let mut params = String::new();
+                let mut final_byte = None;
+                for next in chars.by_ref() {
+                    if ('@'..='~').contains(&next) {
+                        final_byte = Some(next);
+                        break;
+                    }
+                    params.push(next);
+                }
+                if final_byte != Some('m') {
+                    continue;
+                }
+
+                let next_style = style.apply(&params);
+                if next_style != style && span_open {
+                    html.push_str("</span>");
+                    span_open = false;
+                }
+                style = next_style;
+            }
+            '\n' | '\t' => html.push(c),
+            c if c.is_control() => {}
+            c => {
+                // Open spans lazily so style changes without text leave no empty tags
+                if !span_open && style != Style::default() {
+                    let _ = write!(html, "<span style=\"{}\">", style.css());
+                    span_open = true;
+                }
+                match c {
+                    '&' => html.push_str("&amp;"),
+                    '<' => html.push_str("&lt;"),
+                    '>' => html.push_str("&gt;"),
+                    c => html.push(c),
+                }
+            }
+        }
+    }
+
+    if span_open {
+        html.push_str("</span>");
+    }
+    html
+}
+
+/// Text attributes set by SGR sequences
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Style {
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    foreground: Option<(u8, u8, u8)>,
+    background: Option<(u8, u8, u8)>,
+}
+
+impl Style {
+    /// Style after applying the parameters of an SGR sequence
+    fn apply(mut self, params: &str) -> Self {
+        // An empty parameter means 0, so `ESC[m` resets
+        let codes: Vec<u16> = params
+            .split(';')
+            .map(|code| code.parse().unwrap_or(0))
+            .collect();
+
+        let mut index = 0;
+        while index < codes.len() {
+            match codes[index] {
+                0 => self = Style::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                code @ 30..=37 => self.foreground = Some(palette((code - 30) as u8)),
+                code @ 90..=97 => self.foreground = Some(palette((code - 90 + 8) as u8)),
+                39 => self.foreground = None,
+                code @ 40..=47 => self.background = Some(palette((code - 40) as u8)),
+                code @ 100..=107 => self.background = Some(palette((code - 100 + 8) as u8)),
+                49 => self.background = None,
+                code @ (38 | 48) => {
+                    let (color, used) = extended_color(&codes[index + 1..]);
+                    index += used;
+                    if code == 38 {
+                        self.foreground = color;
+                    } else {
+                        self.background = color;
+                    }
+                }
+                _ => {}
+            }
+            index += 1;
+        }
+        self
+    }
+
+    fn css(&self) -> String {
+        let mut css = Vec::new();
+        if let Some((r, g, b)) = self.foreground {
+            css.push(format!("color:#{:02x}{:02x}{:02x}", r, g, b));
+        }
+        if let Some((r, g, b)) = self.background {
+            css.push(format!("background-color:#{:02x}{:02x}{:02x}", r, g, b));
+        }
+        if self.bold {
+            css.push("font-weight:bold".to_string());
+        }
+        if self.dim {
+            css.push("opacity:0.7".to_string());
+        }
+        if self.italic {
+            css.push("font-style:italic".to_string());
+        }
+        if self.underline {
+            css.push("text-decoration:underline".to_string());
+        }
+        css.join(";")
+    }
+}
+
+/// Parses the arguments of a `38`/`48` code: `5;N` or `2;R;G;B`
+///
+/// Returns the color and the number of codes consumed.
+fn extended_color(codes: &[u16]) -> (Option<(u8, u8, u8)>, usize) {
+    match codes {
+        [5, index, ..] => (Some(palette(*index as u8)), 2),
+        [2, r, g, b, ..] => (Some((*r as u8, *g as u8, *b as u8)), 4),
+        _ => (None, codes.len()),
+    }
+}
+
+/// RGB value of an entry of the 256-color palette
+fn palette(index: u8) -> (u8, u8, u8) {
+    const BASE: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0xcd, 0x31, 0x31),
+        (0x0d, 0xbc, 0x79),
+        (0xe5, 0xe5, 0x10),
+        (0x24, 0x72, 0xc8),
+        (0xbc, 0x3f, 0xbc),
+        (0x11, 0xa8, 0xcd),
+        (0xe5, 0xe5, 0xe5),
+        (0x66, 0x66, 0x66),
+        (0xf1, 0x4c, 0x4c),
+        (0x23, 0xd1, 0x8b),
+        (0xf5, 0xf5, 0x43),
+        (0x3b, 0x8e, 0xea),
+        (0xd6, 0x70, 0xd6),
+        (0x29, 0xb8, 0xdb),
+        (0xff, 0xff, 0xff),
+    ];
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    match index {
+        0..=15 => BASE[index as usize],
+        16..=231 => {
+            let cube = index - 16;
+            (
+                LEVELS[(cube / 36) as usize],
+                LEVELS[(cube / 6 % 6) as usize],
+                LEVELS[(cube % 6) as usize],
+            )
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::DiagnosticLevel;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_ansi_to_html() {
+        let html = ansi_to_html("\u{1b}[1m\u{1b}[91mwarning\u{1b}[0m: <Vec>\u{1b}[K\n\u{1b}[38;5;12mnote\u{1b}[m");
+
+        assert_eq!(
+            html,
+            "<span style=\"color:#f14c4c;font-weight:bold\">warning</span>: &lt;Vec&gt;\n\
+             <span style=\"color:#3b8eea\">note</span>"
+        );
+    }
+
+    #[test]
+    fn test_render_html_report() {
+        let duration = Duration::from_millis(250);
+        let mut second = Diagnostic::new(DiagnosticLevel::Warning, "unused variable: `y`");
+        second.file = Some("src/lib.rs".to_string());
+        second.line = Some(9);
+        let mut first = Diagnostic::new(DiagnosticLevel::Warning, "unused variable: `x`");
+        first.file = Some("src/lib.rs".to_string());
+        first.line = Some(3);
+
+        let report = RunReport {
+            started_at: UNIX_EPOCH,
+            duration: Duration::from_secs(1),
+            results: vec![CheckResult {
+                output: vec!["\u{1b}[33mwarning\u{1b}[0m: unused".to_string()],
+                ..CheckResult::for_test("Check", CheckStatus::Warning { warnings: 2, duration })
+                    .with_diagnostics(vec![second, first])
+            }],
+            interrupted: false,
+            exit_code: 1,
+        };
+        let html = render(&report);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Started 1970-01-01T00:00:00.000Z"));
+        assert!(html.contains("<td class=\"status warning\">2 warnings</td>"));
+        assert_eq!(html.matches("<h4><code>src/lib.rs</code></h4>").count(), 1);
+        let x = html.find("unused variable: `x`").unwrap();
+        let y = html.find("unused variable: `y`").unwrap();
+        assert!(x < y);
+        assert!(html.contains("<span style=\"color:#e5e510\">warning</span>: unused"));
+    }
+}
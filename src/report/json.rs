@@ -0,0 +1,208 @@
+//! Versioned JSON report
+//!
+//! The document's `version` is bumped whenever a field is removed or changes
+//! meaning. New fields may be added without a version bump.
+
+use crate::display::CheckStatus;
+use crate::error::{CargoStatusError, Result};
+use crate::report::packages::{package_counts, PackageCounts};
+use crate::report::{rfc3339, status_counts, status_name, RunReport};
+use crate::tools::{CheckResult, Diagnostic};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Version of the JSON report schema
+pub const REPORT_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    version: u32,
+    cargo_status_version: &'static str,
+    started_at: String,
+    duration_ms: u64,
+    interrupted: bool,
+    exit_code: i32,
+    summary: JsonSummary,
+    checks: Vec<JsonCheck<'a>>,
+}
+
+/// Number of checks that finished in each status
+#[derive(Serialize, Default)]
+struct JsonSummary {
+    total: usize,
+    success: usize,
+    warning: usize,
+    error: usize,
+    failed: usize,
+    timed_out: usize,
+    cancelled: usize,
+    skipped: usize,
+}
+
+#[derive(Serialize)]
+struct JsonCheck<'a> {
+    name: &'a str,
+    command: &'a [String],
+    exit_code: Option<i32>,
+    duration_ms: u64,
+    status: &'static str,
+    /// Why the check failed or was skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout_ms: Option<u64>,
+    /// Whether the result was replayed from the result cache
+    cached: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_version: Option<&'a str>,
+    counts: JsonCounts,
+    /// Counts split by the workspace package they belong to
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    packages: BTreeMap<String, PackageCounts>,
+    diagnostics: &'a [Diagnostic],
+}
+
+#[derive(Serialize)]
+struct JsonCounts {
+    errors: usize,
+    warnings: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tests_passed: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tests_failed: Option<usize>,
+}
+
+impl<'a> JsonCheck<'a> {
+    fn new(result: &'a CheckResult) -> Self {
+        let (errors, warnings) = status_counts(&result.status);
+        let (reason, timeout) = match result.status {
+            CheckStatus::Failed { ref reason, .. } | CheckStatus::Skipped { ref reason } => {
+                (Some(reason.as_str()), None)
+            }
+            CheckStatus::TimedOut { timeout, .. } => (None, Some(millis(timeout))),
+            _ => (None, None),
+        };
+
+        Self {
+            name: &result.name,
+            command: &result.command,
+            exit_code: result.exit_code,
+            duration_ms: millis(result.duration),
+            status: status_name(&result.status),
+            reason,
+            timeout_ms: timeout,
+            cached: result.cached,
+            tool_version: result.tool_version.as_deref(),
+            counts: JsonCounts {
+                errors,
+                warnings,
+                tests_passed: result.test_counts().map(|tests| tests.passed),
+                tests_failed: result.test_counts().map(|tests| tests.failed),
+            },
+            packages: package_counts(result),
+            diagnostics: &result.diagnostics,
+        }
+    }
+}
+
+impl JsonSummary {
+    fn new(results: &[CheckResult]) -> Self {
+        let mut summary = Self {
+            total: results.len(),
+            ..Self::default()
+        };
+        for result in results {
+            match result.status {
+                CheckStatus::Success { .. } => summary.success += 1,
+                CheckStatus::Warning { .. } => summary.warning += 1,
+                CheckStatus::Error { .. } => summary.error += 1,
+                CheckStatus::Failed { .. } => summary.failed += 1,
+                CheckStatus::TimedOut { .. } => summary.timed_out += 1,
+                CheckStatus::Cancelled { .. } => summary.cancelled += 1,
+                CheckStatus::Skipped { .. } => summary.skipped += 1,
+                CheckStatus::Pending | CheckStatus::Running { .. } => {}
+            }
+        }
+        summary
+    }
+}
+
+/// Renders the run as a pretty-printed JSON document
+pub fn render(report: &RunReport) -> Result<String> {
+    let document = JsonReport {
+        version: REPORT_VERSION,
+        cargo_status_version: env!("CARGO_PKG_VERSION"),
+        started_at: rfc3339(report.started_at),
+        duration_ms: millis(report.duration),
+        interrupted: report.interrupted,
+        exit_code: report.exit_code,
+        summary: JsonSummary::new(&report.results),
+        checks: report.results.iter().map(JsonCheck::new).collect(),
+    };
+
+    serde_json::to_string_pretty(&document)
+        .map_err(|e| CargoStatusError::other(format!("Failed to serialize JSON report: {}", e)))
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{DiagnosticLevel, TestCounts};
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_render_json_report() {
+        let mut diagnostic = Diagnostic::new(DiagnosticLevel::Warning, "unused variable: `x`");
+        diagnostic.file = Some("src/lib.rs".to_string());
+        diagnostic.line = Some(3);
+
+        let report = RunReport {
+            started_at: UNIX_EPOCH,
+            duration: Duration::from_millis(1500),
+            results: vec![
+                CheckResult {
+                    exit_code: Some(0),
+                    tests: Some(TestCounts { passed: 4, failed: 0 }),
+                    ..CheckResult::for_test(
+                        "Test",
+                        CheckStatus::Warning {
+                            warnings: 1,
+                            duration: Duration::from_millis(1200),
+                        },
+                    )
+                    .with_diagnostics(vec![diagnostic])
+                },
+                CheckResult::for_test(
+                    "Doc",
+                    CheckStatus::Skipped {
+                        reason: "Test did not pass".to_string(),
+                    },
+                ),
+            ],
+            interrupted: false,
+            exit_code: 0,
+        };
+
+        let json: serde_json::Value = serde_json::from_str(&render(&report).unwrap()).unwrap();
+        assert_eq!(json["version"], REPORT_VERSION);
+        assert_eq!(json["started_at"], "1970-01-01T00:00:00.000Z");
+        assert_eq!(json["summary"]["warning"], 1);
+        assert_eq!(json["summary"]["skipped"], 1);
+
+        let test = &json["checks"][0];
+        assert_eq!(test["status"], "warning");
+        assert_eq!(test["exit_code"], 0);
+        assert_eq!(test["counts"]["tests_passed"], 4);
+        assert_eq!(test["diagnostics"][0]["level"], "warning");
+        assert_eq!(test["diagnostics"][0]["line"], 3);
+
+        let doc = &json["checks"][1];
+        assert_eq!(doc["reason"], "Test did not pass");
+        assert!(doc["exit_code"].is_null());
+    }
+}
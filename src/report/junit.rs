@@ -0,0 +1,396 @@
+//! JUnit XML report
+//!
+//! Each check becomes a `<testsuite>`. The test check contributes one
+//! `<testcase>` per test, and compiler-backed checks one per diagnostic.
+//! Checks with neither are reported as a single test case named after the
+//! check, so that failures, timeouts and skips still show up.
+
+use crate::display::CheckStatus;
+use crate::report::{rfc3339, RunReport};
+use crate::tools::diagnostics::strip_ansi;
+use crate::tools::{CheckResult, Diagnostic, DiagnosticLevel, TestCase, TestOutcome};
+use std::fmt::Write;
+use std::time::Duration;
+
+/// A `<testcase>` element
+struct JunitCase {
+    name: String,
+    classname: String,
+    time: Duration,
+    file: Option<String>,
+    line: Option<usize>,
+    outcome: JunitOutcome,
+}
+
+/// How a test case ended
+enum JunitOutcome {
+    Passed,
+    /// The check found a problem (test failure, error or warning)
+    Failure { kind: String, message: String, body: String },
+    /// The check itself could not run to completion
+    Error { kind: String, message: String },
+    Skipped { message: String },
+}
+
+/// Renders the run as a JUnit XML document
+pub fn render(report: &RunReport) -> String {
+    let suites: Vec<(&CheckResult, Vec<JunitCase>)> = report
+        .results
+        .iter()
+        .map(|result| (result, test_cases(result)))
+        .collect();
+
+    let count = |matches: fn(&JunitOutcome) -> bool| -> usize {
+        suites
+            .iter()
+            .flat_map(|(_, cases)| cases)
+            .filter(|case| matches(&case.outcome))
+            .count()
+    };
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"cargo-status\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{}\" timestamp=\"{}\">",
+        suites.iter().map(|(_, cases)| cases.len()).sum::<usize>(),
+        count(|outcome| matches!(outcome, JunitOutcome::Failure { .. })),
+        count(|outcome| matches!(outcome, JunitOutcome::Error { .. })),
+        count(|outcome| matches!(outcome, JunitOutcome::Skipped { .. })),
+        seconds(report.duration),
+        rfc3339(report.started_at)
+    );
+    for (result, cases) in &suites {
+        write_suite(&mut xml, result, cases);
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn write_suite(xml: &mut String, result: &CheckResult, cases: &[JunitCase]) {
+    let count = |matches: fn(&JunitOutcome) -> bool| {
+        cases.iter().filter(|case| matches(&case.outcome)).count()
+    };
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{}\">",
+        escape(&result.name),
+        cases.len(),
+        count(|outcome| matches!(outcome, JunitOutcome::Failure { .. })),
+        count(|outcome| matches!(outcome, JunitOutcome::Error { .. })),
+        count(|outcome| matches!(outcome, JunitOutcome::Skipped { .. })),
+        seconds(result.duration)
+    );
+    let _ = writeln!(
+        xml,
+        "    <properties>\n      <property name=\"command\" value=\"{}\"/>\n    </properties>",
+        escape(&result.command.join(" "))
+    );
+
+    for case in cases {
+        let mut attributes = format!(
+            "name=\"{}\" classname=\"{}\" time=\"{}\"",
+            escape(&case.name),
+            escape(&case.classname),
+            seconds(case.time)
+        );
+        if let Some(ref file) = case.file {
+            let _ = write!(attributes, " file=\"{}\"", escape(file));
+        }
+        if let Some(line) = case.line {
+            let _ = write!(attributes, " line=\"{}\"", line);
+        }
+
+        match case.outcome {
+            JunitOutcome::Passed => {
+                let _ = writeln!(xml, "    <testcase {}/>", attributes);
+            }
+            JunitOutcome::Failure {
+                ref kind,
+                ref message,
+                ref body,
+            } => {
+                let _ = writeln!(
+                    xml,
+                    "    <testcase {}>\n      <failure type=\"{}\" message=\"{}\">{}</failure>\n    </testcase>",
+                    attributes,
+                    escape(kind),
+                    escape(message),
+                    escape(body)
+                );
+            }
+            JunitOutcome::Error {
+                ref kind,
+                ref message,
+            } => {
+                let _ = writeln!(
+                    xml,
+                    "    <testcase {}>\n      <error type=\"{}\" message=\"{}\"/>\n    </testcase>",
+                    attributes,
+                    escape(kind),
+                    escape(message)
+                );
+            }
+            JunitOutcome::Skipped { ref message } => {
+                let _ = writeln!(
+                    xml,
+                    "    <testcase {}>\n      <skipped message=\"{}\"/>\n    </testcase>",
+                    attributes,
+                    escape(message)
+                );
+            }
+        }
+    }
+    xml.push_str("  </testsuite>\n");
+}
+
+/// Test cases for a check: its tests, its diagnostics, or the check itself
+fn test_cases(result: &CheckResult) -> Vec<JunitCase> {
+    let interrupted = matches!(
+        result.status,
+        CheckStatus::Failed { .. }
+            | CheckStatus::TimedOut { .. }
+            | CheckStatus::Cancelled { .. }
+            | CheckStatus::Skipped { .. }
+    );
+
+    // A check that didn't finish may only have partial results
+    let mut cases: Vec<JunitCase> = if !result.test_cases.is_empty() {
+        result.test_cases.iter().map(|case| test_case(result, case)).collect()
+    } else {
+        result
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic_case(result, diagnostic))
+            .collect()
+    };
+    if cases.is_empty() || interrupted {
+        cases.push(check_case(result));
+    }
+    cases
+}
+
+fn test_case(result: &CheckResult, case: &TestCase) -> JunitCase {
+    let outcome = match case.outcome {
+        TestOutcome::Passed => JunitOutcome::Passed,
+        TestOutcome::Failed => {
+            let body = case.message.clone().unwrap_or_default();
+            JunitOutcome::Failure {
+                kind: "test failure".to_string(),
+                message: body.lines().next().unwrap_or("test failed").to_string(),
+                body,
+            }
+        }
+        TestOutcome::Ignored => JunitOutcome::Skipped {
+            message: "ignored".to_string(),
+        },
+    };
+
+    JunitCase {
+        name: case.name.clone(),
+        classname: case.suite.clone().unwrap_or_else(|| result.name.clone()),
+        time: case.duration.unwrap_or_default(),
+        file: None,
+        line: None,
+        outcome,
+    }
+}
+
+fn diagnostic_case(result: &CheckResult, diagnostic: &Diagnostic) -> JunitCase {
+    let location = match (&diagnostic.file, diagnostic.line, diagnostic.column) {
+        (Some(file), Some(line), Some(column)) => format!("{}:{}:{}", file, line, column),
+        (Some(file), Some(line), None) => format!("{}:{}", file, line),
+        (Some(file), None, _) => file.clone(),
+        (None, ..) => String::new(),
+    };
+    let name = if location.is_empty() {
+        diagnostic.message.clone()
+    } else {
+        format!("{}: {}", location, diagnostic.message)
+    };
+    let classname = match diagnostic.code {
+        Some(ref code) => format!("{}.{}", result.name, code),
+        None => result.name.clone(),
+    };
+
+    JunitCase {
+        name,
+        classname,
+        time: Duration::ZERO,
+        file: diagnostic.file.clone(),
+        line: diagnostic.line,
+        outcome: JunitOutcome::Failure {
+            kind: diagnostic.level.as_str().to_string(),
+            message: diagnostic.message.clone(),
+            body: match diagnostic.level {
+                DiagnosticLevel::Error => format!("error: {}", diagnostic.message),
+                DiagnosticLevel::Warning => format!("warning: {}", diagnostic.message),
+            },
+        },
+    }
+}
+
+/// A single test case standing for the whole check
+fn check_case(result: &CheckResult) -> JunitCase {
+    let outcome = match result.status {
+        CheckStatus::Error {
+            errors, warnings, ..
+        } => JunitOutcome::Failure {
+            kind: "error".to_string(),
+            message: format!("{} errors, {} warnings", errors, warnings),
+            body: String::new(),
+        },
+        CheckStatus::Warning { warnings, .. } => JunitOutcome::Failure {
+            kind: "warning".to_string(),
+            message: format!("{} warnings", warnings),
+            body: String::new(),
+        },
+        CheckStatus::Failed { ref reason, .. } => JunitOutcome::Error {
+            kind: "failed".to_string(),
+            message: reason.clone(),
+        },
+        CheckStatus::TimedOut { timeout, .. } => JunitOutcome::Error {
+            kind: "timeout".to_string(),
+            message: format!("timed out after {}s", timeout.as_secs()),
+        },
+        CheckStatus::Cancelled { .. } => JunitOutcome::Skipped {
+            message: "cancelled".to_string(),
+        },
+        CheckStatus::Skipped { ref reason } => JunitOutcome::Skipped {
+            message: reason.clone(),
+        },
+        CheckStatus::Success { .. } | CheckStatus::Pending | CheckStatus::Running { .. } => {
+            JunitOutcome::Passed
+        }
+    };
+
+    JunitCase {
+        name: result.name.clone(),
+        classname: "cargo-status".to_string(),
+        time: result.duration,
+        file: None,
+        line: None,
+        outcome,
+    }
+}
+
+/// Formats a duration as seconds with millisecond precision
+fn seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+/// Escapes text for use in XML attributes and element content
+///
+/// ANSI color codes and other characters that are not allowed in XML 1.0
+/// are dropped.
+fn escape(text: &str) -> String {
+    let text = strip_ansi(text);
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_render_junit_report() {
+        let mut clippy = CheckResult::for_test(
+            "Clippy",
+            CheckStatus::Error {
+                errors: 1,
+                warnings: 0,
+                duration: Duration::from_millis(800),
+            },
+        );
+        let mut diagnostic = Diagnostic::new(DiagnosticLevel::Error, "this `if` has identical <blocks>");
+        diagnostic.code = Some("clippy::if_same_then_else".to_string());
+        diagnostic.file = Some("src/main.rs".to_string());
+        diagnostic.line = Some(4);
+        diagnostic.column = Some(5);
+        clippy.diagnostics.push(diagnostic);
+
+        let mut test = CheckResult::for_test(
+            "Test",
+            CheckStatus::Error {
+                errors: 1,
+                warnings: 0,
+                duration: Duration::from_secs(2),
+            },
+        );
+        test.test_cases = vec![
+            TestCase {
+                name: "tests::adds".to_string(),
+                suite: None,
+                crate_name: None,
+                outcome: TestOutcome::Passed,
+                duration: Some(Duration::from_millis(5)),
+                message: None,
+            },
+            TestCase {
+                name: "tests::divides".to_string(),
+                suite: None,
+                crate_name: None,
+                outcome: TestOutcome::Failed,
+                duration: None,
+                message: Some("attempt to divide by zero".to_string()),
+            },
+        ];
+
+        let doc = CheckResult::for_test(
+            "Doc",
+            CheckStatus::Skipped {
+                reason: "Test did not pass".to_string(),
+            },
+        );
+
+        let report = RunReport {
+            started_at: UNIX_EPOCH,
+            duration: Duration::from_secs(3),
+            results: vec![clippy, test, doc],
+            interrupted: false,
+            exit_code: 1,
+        };
+        let xml = render(&report);
+
+        let document = roxmltree::Document::parse(&xml).unwrap();
+        let root = document.root_element();
+        assert_eq!(root.attribute("tests"), Some("4"));
+        assert_eq!(root.attribute("failures"), Some("2"));
+        assert_eq!(root.attribute("skipped"), Some("1"));
+
+        let suites: Vec<_> = root.children().filter(|node| node.has_tag_name("testsuite")).collect();
+        assert_eq!(suites.len(), 3);
+
+        let lint = suites[0].children().find(|node| node.has_tag_name("testcase")).unwrap();
+        assert_eq!(lint.attribute("classname"), Some("Clippy.clippy::if_same_then_else"));
+        assert_eq!(
+            lint.attribute("name"),
+            Some("src/main.rs:4:5: this `if` has identical <blocks>")
+        );
+
+        let failed = suites[1]
+            .descendants()
+            .find(|node| node.has_tag_name("failure"))
+            .unwrap();
+        assert_eq!(failed.text(), Some("attempt to divide by zero"));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a < b && \"c\""), "a &lt; b &amp;&amp; &quot;c&quot;");
+        assert_eq!(escape("\u{1b}[31mred\u{1b}[0m\u{7}"), "red");
+    }
+}
@@ -0,0 +1,224 @@
+//! Markdown summary for pull request comments and CI job summaries
+//!
+//! A table with one row per check is followed by a collapsible section for
+//! each check that did not pass cleanly, listing its first diagnostics,
+//! failed tests or the reason it failed.
+
+use crate::display::{format_duration, CheckStatus};
+use crate::report::packages::{cell, package_counts, package_names};
+use crate::report::{status_counts, status_summary, RunReport};
+use crate::tools::{CheckResult, Diagnostic, DiagnosticLevel, TestOutcome};
+use std::fmt::Write;
+
+/// Number of diagnostics listed for each check
+pub const DIAGNOSTICS_PER_CHECK: usize = 10;
+
+/// Renders the run as Markdown
+pub fn render(report: &RunReport) -> String {
+    let passed = report
+        .results
+        .iter()
+        .all(|result| matches!(result.status, CheckStatus::Success { .. }));
+
+    let mut markdown = format!(
+        "## {} cargo status\n\n",
+        if passed { "✅" } else { "❌" }
+    );
+    markdown.push_str("| Check | Status | Errors | Warnings | Duration |\n");
+    markdown.push_str("| --- | --- | ---: | ---: | ---: |\n");
+    for result in &report.results {
+        let (errors, warnings) = status_counts(&result.status);
+        let _ = writeln!(
+            markdown,
+            "| {} | {} {} | {} | {} | {} |",
+            result.name,
+            status_icon(&result.status),
+            status_summary(&result.status).replace('|', "\\|"),
+            errors,
+            warnings,
+            format_duration(result.duration)
+        );
+    }
+
+    // Same wording as the terminal summary
+    let completed = report
+        .results
+        .iter()
+        .filter(|result| !matches!(result.status, CheckStatus::Cancelled { .. }))
+        .count();
+    if report.interrupted {
+        let _ = writeln!(
+            markdown,
+            "\nCancelled after {} ({} of {} checks completed)",
+            format_duration(report.duration),
+            completed,
+            report.results.len()
+        );
+    } else {
+        let _ = writeln!(markdown, "\nCompleted in {}", format_duration(report.duration));
+    }
+    package_table(&mut markdown, &report.results);
+
+    for result in &report.results {
+        if let Some(details) = details(result) {
+            let _ = write!(
+                markdown,
+                "\n<details>\n<summary><b>{}</b>: {}</summary>\n\n{}\n</details>\n",
+                result.name,
+                escape_html(&status_summary(&result.status)),
+                details
+            );
+        }
+    }
+    markdown
+}
+
+/// Crate-by-check table, when the checks reported on several packages
+fn package_table(markdown: &mut String, results: &[CheckResult]) {
+    let packages = package_names(results);
+    if packages.len() < 2 {
+        return;
+    }
+    let counts: Vec<_> = results.iter().map(package_counts).collect();
+    markdown.push_str("\n### Packages\n\n| Package |");
+    for result in results {
+        let _ = write!(markdown, " {} |", result.name);
+    }
+    let _ = write!(markdown, "\n| --- |{}\n", " --- |".repeat(results.len()));
+    for package in &packages {
+        let _ = write!(markdown, "| {} |", package);
+        for (result, counts) in results.iter().zip(&counts) {
+            let _ = write!(markdown, " {} |", cell(result, counts.get(package)));
+        }
+        markdown.push('\n');
+    }
+}
+
+/// Body of the collapsible section for a check that did not pass cleanly
+fn details(result: &CheckResult) -> Option<String> {
+    let mut body = String::new();
+    match result.status {
+        CheckStatus::Success { .. } | CheckStatus::Skipped { .. } | CheckStatus::Cancelled { .. } => {
+            return None;
+        }
+        // Failure reasons may span several lines (e.g. a process tree)
+        CheckStatus::Failed { ref reason, .. } => {
+            let _ = writeln!(body, "```\n{}\n```", reason);
+        }
+        _ => {}
+    }
+
+    let failed_tests: Vec<_> = result
+        .test_cases
+        .iter()
+        .filter(|case| case.outcome == TestOutcome::Failed)
+        .collect();
+    for case in failed_tests.iter().take(DIAGNOSTICS_PER_CHECK) {
+        let _ = writeln!(body, "- ❌ test `{}` failed", case.name);
+    }
+    if failed_tests.len() > DIAGNOSTICS_PER_CHECK {
+        let _ = writeln!(
+            body,
+            "- …and {} more failed tests",
+            failed_tests.len() - DIAGNOSTICS_PER_CHECK
+        );
+    }
+
+    for diagnostic in result.diagnostics.iter().take(DIAGNOSTICS_PER_CHECK) {
+        let _ = writeln!(body, "- {}", diagnostic_item(diagnostic));
+    }
+    if result.diagnostics.len() > DIAGNOSTICS_PER_CHECK {
+        let _ = writeln!(
+            body,
+            "- …and {} more",
+            result.diagnostics.len() - DIAGNOSTICS_PER_CHECK
+        );
+    }
+
+    if body.is_empty() {
+        let _ = writeln!(body, "No diagnostics were reported.");
+    }
+    Some(body)
+}
+
+fn diagnostic_item(diagnostic: &Diagnostic) -> String {
+    let icon = match diagnostic.level {
+        DiagnosticLevel::Error => "❌",
+        DiagnosticLevel::Warning => "⚠️",
+    };
+    let mut item = icon.to_string();
+    if let Some(ref file) = diagnostic.file {
+        let location = match (diagnostic.line, diagnostic.column) {
+            (Some(line), Some(column)) => format!("{}:{}:{}", file, line, column),
+            (Some(line), None) => format!("{}:{}", file, line),
+            _ => file.clone(),
+        };
+        let _ = write!(item, " `{}`", location);
+    }
+    let _ = write!(item, " {}", escape_html(&diagnostic.message));
+    if let Some(ref code) = diagnostic.code {
+        let _ = write!(item, " (`{}`)", code);
+    }
+    item
+}
+
+/// Emoji shown for a status in Markdown
+pub fn status_icon(status: &CheckStatus) -> &'static str {
+    match status {
+        CheckStatus::Success { .. } => "✅",
+        CheckStatus::Warning { .. } => "⚠️",
+        CheckStatus::Error { .. } | CheckStatus::Failed { .. } => "❌",
+        CheckStatus::TimedOut { .. } => "⏱️",
+        CheckStatus::Cancelled { .. } => "⊘",
+        CheckStatus::Skipped { .. } => "↷",
+        CheckStatus::Pending | CheckStatus::Running { .. } => "…",
+    }
+}
+
+/// Keeps text from being read as HTML tags inside `<details>`
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_render_markdown_report() {
+        let duration = Duration::from_millis(250);
+        let diagnostics: Vec<Diagnostic> = (1..=12)
+            .map(|line| {
+                let mut diagnostic =
+                    Diagnostic::new(DiagnosticLevel::Warning, "unused `Vec<u8>`");
+                diagnostic.file = Some("src/lib.rs".to_string());
+                diagnostic.line = Some(line);
+                diagnostic.crate_name = Some(if line <= 9 { "api" } else { "core" }.to_string());
+                diagnostic
+            })
+            .collect();
+
+        let report = RunReport {
+            started_at: UNIX_EPOCH,
+            duration: Duration::from_secs(2),
+            results: vec![
+                CheckResult::for_test("Format", CheckStatus::Success { warnings: 0, duration }),
+                CheckResult::for_test("Check", CheckStatus::Warning { warnings: 12, duration })
+                    .with_diagnostics(diagnostics),
+            ],
+            interrupted: false,
+            exit_code: 1,
+        };
+        let markdown = render(&report);
+
+        assert!(markdown.starts_with("## ❌ cargo status\n"));
+        assert!(markdown.contains("| Format | ✅ passed | 0 | 0 | 250ms |"));
+        assert!(markdown.contains("| Check | ⚠️ 12 warnings | 0 | 12 | 250ms |"));
+        assert!(markdown.contains("Completed in 2.0s"));
+        assert!(markdown.contains("| Package | Format | Check |\n| --- | --- | --- |\n"));
+        assert!(markdown.contains("| api | ✓ | 9 warnings |\n| core | ✓ | 3 warnings |\n"));
+        assert_eq!(markdown.matches("<details>").count(), 1);
+        assert!(markdown.contains("- ⚠️ `src/lib.rs:10` unused `Vec&lt;u8&gt;`\n- …and 2 more"));
+    }
+}
@@ -0,0 +1,246 @@
+//! Machine-readable reports of a cargo-status run
+//!
+//! The terminal display is meant for people. Reports describe the same run in
+//! a format other tools can consume, written to stdout or to a file once all
+//! checks have finished.
+
+pub mod badge;
+pub mod ci;
+pub mod github;
+pub mod gitlab;
+pub mod html;
+pub mod json;
+pub mod junit;
+pub mod markdown;
+pub mod packages;
+pub mod sarif;
+
+use crate::display::{format_duration, CheckStatus};
+use crate::error::{CargoStatusError, Result};
+use crate::tools::registry::ToolRegistry;
+use crate::tools::{CheckResult, Diagnostic};
+use crate::workspace::Workspace;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Format of a machine-readable report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReportFormat {
+    /// Versioned JSON document with every check's results
+    Json,
+    /// JUnit XML with one test suite per check
+    Junit,
+    /// SARIF 2.1.0 log of compiler and clippy diagnostics
+    Sarif,
+    /// GitLab Code Quality issues
+    Gitlab,
+    /// Summary table and collapsible details for pull request comments
+    Markdown,
+    /// Standalone HTML page with each check's diagnostics and colored output
+    Html,
+}
+
+/// Everything a report describes about a finished run
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    pub started_at: SystemTime,
+    pub duration: Duration,
+    /// Results in display order
+    pub results: Vec<CheckResult>,
+    /// Whether the run was cancelled before all checks finished
+    pub interrupted: bool,
+    pub exit_code: i32,
+}
+
+impl RunReport {
+    /// Renders the report in the given format
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Json => json::render(self),
+            ReportFormat::Junit => Ok(junit::render(self)),
+            ReportFormat::Sarif => sarif::render(self),
+            ReportFormat::Gitlab => gitlab::render(self),
+            ReportFormat::Markdown => Ok(markdown::render(self)),
+            ReportFormat::Html => Ok(html::render(self)),
+        }
+    }
+
+    /// Saves the report as the last run for `cargo status badge`, at
+    /// [`last_run_path`]
+    pub fn save_last_run(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|source| CargoStatusError::Io {
+                context: format!("Failed to create {}", parent.display()),
+                source,
+            })?;
+        }
+        self.write(ReportFormat::Json, Some(path))
+    }
+
+    /// Writes the report to `output`, or to stdout if no file is given
+    pub fn write(&self, format: ReportFormat, output: Option<&Path>) -> Result<()> {
+        let rendered = self.render(format)?;
+        match output {
+            Some(path) => std::fs::write(path, rendered).map_err(|source| CargoStatusError::Io {
+                context: format!("Failed to write report to {}", path.display()),
+                source,
+            }),
+            None => {
+                println!("{}", rendered.trim_end());
+                Ok(())
+            }
+        }
+    }
+}
+
+/// JSON report of the most recent run in `workspace`, read by `cargo status badge`
+pub fn last_run_path(workspace: Option<&Workspace>) -> PathBuf {
+    ToolRegistry::status_dir(workspace).join("last-run.json")
+}
+
+/// Diagnostics of every check, each with the name of the first check that reported it
+///
+/// Check, Clippy and Build compile the same code and report the same rustc
+/// warnings, so a diagnostic seen in an earlier check is left out.
+pub fn unique_diagnostics(report: &RunReport) -> Vec<(&str, &Diagnostic)> {
+    let mut seen = HashSet::new();
+    report
+        .results
+        .iter()
+        .flat_map(|result| {
+            result
+                .diagnostics
+                .iter()
+                .map(move |diagnostic| (result.name.as_str(), diagnostic))
+        })
+        .filter(|(_, diagnostic)| seen.insert(diagnostic.dedup_key()))
+        .collect()
+}
+
+/// Stable lowercase name of a check status, as used in reports
+pub fn status_name(status: &CheckStatus) -> &'static str {
+    match status {
+        CheckStatus::Pending => "pending",
+        CheckStatus::Running { .. } => "running",
+        CheckStatus::Success { .. } => "success",
+        CheckStatus::Warning { .. } => "warning",
+        CheckStatus::Error { .. } => "error",
+        CheckStatus::Failed { .. } => "failed",
+        CheckStatus::TimedOut { .. } => "timed_out",
+        CheckStatus::Cancelled { .. } => "cancelled",
+        CheckStatus::Skipped { .. } => "skipped",
+    }
+}
+
+/// Error and warning counts shown for a check status
+pub fn status_counts(status: &CheckStatus) -> (usize, usize) {
+    match *status {
+        CheckStatus::Success { warnings, .. } | CheckStatus::Warning { warnings, .. } => {
+            (0, warnings)
+        }
+        CheckStatus::Error {
+            errors, warnings, ..
+        } => (errors, warnings),
+        _ => (0, 0),
+    }
+}
+
+/// Short description of a finished check's status, e.g. `2 errors, 1 warning`
+pub fn status_summary(status: &CheckStatus) -> String {
+    let plural = |count: usize, noun: &str| {
+        format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+    };
+    match status {
+        CheckStatus::Pending => "pending".to_string(),
+        CheckStatus::Running { .. } => "running".to_string(),
+        CheckStatus::Success { .. } => "passed".to_string(),
+        CheckStatus::Warning { warnings, .. } => plural(*warnings, "warning"),
+        CheckStatus::Error {
+            errors, warnings, ..
+        } => format!("{}, {}", plural(*errors, "error"), plural(*warnings, "warning")),
+        CheckStatus::Failed { reason, .. } => {
+            format!("failed: {}", reason.lines().next().unwrap_or_default())
+        }
+        CheckStatus::TimedOut { timeout, .. } => {
+            format!("timed out after {}", format_duration(*timeout))
+        }
+        CheckStatus::Cancelled { .. } => "cancelled".to_string(),
+        CheckStatus::Skipped { reason } => format!("skipped: {}", reason),
+    }
+}
+
+/// Formats a time as an RFC 3339 timestamp in UTC with millisecond precision
+pub fn rfc3339(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let secs_of_day = secs % 86_400;
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Converts days since 1970-01-01 into a (year, month, day) date
+///
+/// Howard Hinnant's `civil_from_days` algorithm for the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::DiagnosticLevel;
+
+    #[test]
+    fn test_unique_diagnostics() {
+        let warning = Diagnostic::new(DiagnosticLevel::Warning, "unused variable: `x`");
+        let other = Diagnostic::new(DiagnosticLevel::Warning, "unused import");
+        let duration = Duration::from_secs(1);
+        let status = CheckStatus::Warning { warnings: 1, duration };
+        let report = RunReport {
+            started_at: UNIX_EPOCH,
+            duration,
+            results: vec![
+                CheckResult::for_test("Check", status.clone()).with_diagnostics(vec![warning.clone()]),
+                CheckResult::for_test("Clippy", status.clone())
+                    .with_diagnostics(vec![warning.clone(), other.clone()]),
+                CheckResult::for_test("Build", status).with_diagnostics(vec![warning]),
+            ],
+            interrupted: false,
+            exit_code: 0,
+        };
+
+        let unique = unique_diagnostics(&report);
+        assert_eq!(unique.len(), 2);
+        assert_eq!(unique[0].0, "Check");
+        assert_eq!(unique[1], ("Clippy", &other));
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        let time = UNIX_EPOCH + Duration::from_millis(1_709_210_096_789);
+        assert_eq!(rfc3339(time), "2024-02-29T12:34:56.789Z");
+    }
+}
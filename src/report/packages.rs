@@ -0,0 +1,208 @@
+//! Results broken down by workspace package
+//!
+//! Diagnostics and test cases carry the package they belong to, so a check's
+//! totals can be split into one row per crate. Reports add a per-package
+//! section when a run spans several packages, and `--per-package` prints the
+//! same breakdown as a crate-by-check matrix below the terminal summary.
+
+use crate::display::CheckStatus;
+use crate::tools::{CheckResult, DiagnosticLevel, TestOutcome};
+use colored::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Diagnostics and test results of one check in one package
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PackageCounts {
+    pub errors: usize,
+    pub warnings: usize,
+    pub tests_passed: usize,
+    pub tests_failed: usize,
+}
+
+impl PackageCounts {
+    /// Short description, e.g. `1 error, 3 passed`; empty when clean
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.errors > 0 {
+            parts.push(plural(self.errors, "error"));
+        }
+        if self.warnings > 0 {
+            parts.push(plural(self.warnings, "warning"));
+        }
+        if self.tests_failed > 0 {
+            parts.push(format!("{} failed", self.tests_failed));
+        }
+        if self.tests_passed > 0 {
+            parts.push(format!("{} passed", self.tests_passed));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Counts of a check for each package its diagnostics and tests belong to
+pub fn package_counts(result: &CheckResult) -> BTreeMap<String, PackageCounts> {
+    let mut counts: BTreeMap<String, PackageCounts> = BTreeMap::new();
+    for diagnostic in &result.diagnostics {
+        if let Some(ref package) = diagnostic.crate_name {
+            let entry = counts.entry(package.clone()).or_default();
+            match diagnostic.level {
+                DiagnosticLevel::Error => entry.errors += 1,
+                DiagnosticLevel::Warning => entry.warnings += 1,
+            }
+        }
+    }
+    for case in &result.test_cases {
+        if let Some(ref package) = case.crate_name {
+            let entry = counts.entry(package.clone()).or_default();
+            match case.outcome {
+                TestOutcome::Passed => entry.tests_passed += 1,
+                TestOutcome::Failed => entry.tests_failed += 1,
+                TestOutcome::Ignored => {}
+            }
+        }
+    }
+    counts
+}
+
+/// Every package any check reported something for, sorted by name
+pub fn package_names(results: &[CheckResult]) -> Vec<String> {
+    let names: BTreeSet<String> = results
+        .iter()
+        .flat_map(|result| {
+            let diagnostics = result.diagnostics.iter().filter_map(|d| d.crate_name.clone());
+            let tests = result.test_cases.iter().filter_map(|case| case.crate_name.clone());
+            diagnostics.chain(tests).collect::<Vec<_>>()
+        })
+        .collect();
+    names.into_iter().collect()
+}
+
+/// Text of a matrix cell: the counts, `✓` for a clean package, or `-` when
+/// the check didn't finish
+pub fn cell(result: &CheckResult, counts: Option<&PackageCounts>) -> String {
+    let finished = matches!(
+        result.status,
+        CheckStatus::Success { .. } | CheckStatus::Warning { .. } | CheckStatus::Error { .. }
+    );
+    match counts.map(PackageCounts::summary) {
+        Some(summary) if !summary.is_empty() => summary,
+        _ if finished => "✓".to_string(),
+        _ => "-".to_string(),
+    }
+}
+
+/// Renders a colored crate-by-check matrix for the terminal
+///
+/// `packages` lists the rows, usually every workspace member; packages the
+/// checks reported something for are added if missing.
+pub fn render_matrix(results: &[CheckResult], packages: &[String]) -> String {
+    let mut rows: BTreeSet<String> = packages.iter().cloned().collect();
+    rows.extend(package_names(results));
+    if rows.is_empty() {
+        return String::new();
+    }
+
+    let counts: Vec<_> = results.iter().map(package_counts).collect();
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|package| {
+            results
+                .iter()
+                .zip(&counts)
+                .map(|(result, counts)| cell(result, counts.get(package)))
+                .collect()
+        })
+        .collect();
+
+    // Pad before coloring, so escape codes don't count towards the width
+    let name_width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0).max(7);
+    let widths: Vec<usize> = results
+        .iter()
+        .enumerate()
+        .map(|(column, result)| {
+            cells
+                .iter()
+                .map(|row| row[column].chars().count())
+                .chain([result.name.chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut matrix = format!("{:<name_width$}", "Package").bold().to_string();
+    for (result, width) in results.iter().zip(&widths) {
+        matrix.push_str(&format!("  {:<width$}", result.name).bold().to_string());
+    }
+    matrix.push('\n');
+    for (package, row) in rows.iter().zip(&cells) {
+        matrix.push_str(&format!("{:<name_width$}", package));
+        for (column, text) in row.iter().enumerate() {
+            let padded = format!("  {:<width$}", text, width = widths[column]);
+            let colored = match counts[column].get(package) {
+                Some(counts) if counts.errors > 0 || counts.tests_failed > 0 => padded.red(),
+                Some(counts) if counts.warnings > 0 => padded.yellow(),
+                _ if text == "✓" => padded.green(),
+                _ => padded.dimmed(),
+            };
+            matrix.push_str(&colored.to_string());
+        }
+        matrix.push('\n');
+    }
+    matrix
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::diagnostics::strip_ansi;
+    use crate::tools::{Diagnostic, TestCase};
+    use std::time::Duration;
+
+    #[test]
+    fn test_package_matrix() {
+        let duration = Duration::from_secs(1);
+        let mut clippy = CheckResult::for_test("Clippy", CheckStatus::Warning { warnings: 2, duration });
+        for package in ["api", "api"] {
+            let mut diagnostic = Diagnostic::new(DiagnosticLevel::Warning, "needless return");
+            diagnostic.crate_name = Some(package.to_string());
+            clippy.diagnostics.push(diagnostic);
+        }
+        let mut test = CheckResult::for_test(
+            "Test",
+            CheckStatus::Error {
+                errors: 1,
+                warnings: 0,
+                duration,
+            },
+        );
+        for (package, outcome) in [("core", TestOutcome::Passed), ("core", TestOutcome::Failed)] {
+            test.test_cases.push(TestCase {
+                name: "tests::adds".to_string(),
+                suite: None,
+                crate_name: Some(package.to_string()),
+                outcome,
+                duration: None,
+                message: None,
+            });
+        }
+        let doc = CheckResult::for_test("Doc", CheckStatus::Skipped { reason: "Test did not pass".to_string() });
+        let results = vec![clippy, test, doc];
+
+        assert_eq!(package_names(&results), vec!["api", "core"]);
+        let counts = package_counts(&results[1]);
+        assert_eq!(counts["core"].summary(), "1 failed, 1 passed");
+        assert_eq!(cell(&results[0], package_counts(&results[0]).get("core")), "✓");
+        assert_eq!(cell(&results[2], None), "-");
+
+        let matrix = strip_ansi(&render_matrix(&results, &["cli".to_string()]));
+        let lines: Vec<_> = matrix.lines().collect();
+        assert_eq!(lines[0], "Package  Clippy      Test                Doc");
+        assert_eq!(lines[1], "api      2 warnings  ✓                   -  ");
+        assert_eq!(lines[3], "core     ✓           1 failed, 1 passed  -  ");
+    }
+}
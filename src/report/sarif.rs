@@ -0,0 +1,285 @@
+//! SARIF 2.1.0 report for compiler-backed checks
+//!
+//! Each of the Check, Clippy, Build and Doc checks that ran to completion
+//! becomes one SARIF run. Runs are told apart by their automation ID so that
+//! code-scanning dashboards can track each check's results separately, and a
+//! run without results tells them that earlier alerts have been fixed.
+
+use crate::cache::tools::ToolVersion;
+use crate::display::CheckStatus;
+use crate::error::{CargoStatusError, Result};
+use crate::report::RunReport;
+use crate::tools::{CheckResult, Diagnostic};
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// JSON schema of SARIF 2.1.0 documents
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Checks whose diagnostics come from rustc, clippy or rustdoc
+const COMPILER_CHECKS: [&str; 4] = ["Check", "Clippy", "Build", "Doc"];
+
+/// Renders the diagnostics of compiler-backed checks as a SARIF log
+pub fn render(report: &RunReport) -> Result<String> {
+    let runs: Vec<Value> = report
+        .results
+        .iter()
+        .filter(|result| COMPILER_CHECKS.contains(&result.name.as_str()))
+        .filter(|result| completed(&result.status))
+        .map(sarif_run)
+        .collect();
+
+    let log = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": runs,
+    });
+
+    serde_json::to_string_pretty(&log)
+        .map_err(|e| CargoStatusError::other(format!("Failed to serialize SARIF report: {}", e)))
+}
+
+/// Whether the check ran far enough for its diagnostics to be complete
+fn completed(status: &CheckStatus) -> bool {
+    matches!(
+        status,
+        CheckStatus::Success { .. } | CheckStatus::Warning { .. } | CheckStatus::Error { .. }
+    )
+}
+
+fn sarif_run(result: &CheckResult) -> Value {
+    // One rule per lint name or error code, in order of first appearance
+    let mut rules: Vec<(String, &Diagnostic)> = Vec::new();
+    let mut results = Vec::new();
+    for diagnostic in &result.diagnostics {
+        let rule_id = rule_id(diagnostic);
+        let rule_index = match rules.iter().position(|(id, _)| *id == rule_id) {
+            Some(index) => index,
+            None => {
+                rules.push((rule_id.clone(), diagnostic));
+                rules.len() - 1
+            }
+        };
+        results.push(sarif_result(diagnostic, rule_id, rule_index));
+    }
+
+    let (name, information_uri) = if result.name == "Clippy" {
+        ("clippy", "https://github.com/rust-lang/rust-clippy")
+    } else {
+        ("rustc", "https://doc.rust-lang.org/rustc/")
+    };
+
+    let mut driver = json!({
+        "name": name,
+        "informationUri": information_uri,
+        "rules": rules
+            .iter()
+            .map(|(id, diagnostic)| sarif_rule(id, diagnostic))
+            .collect::<Vec<_>>(),
+    });
+    if let Some(ref version) = result.tool_version {
+        driver["fullName"] = json!(version);
+        if let Some(number) = ToolVersion::parse(version) {
+            driver["semanticVersion"] = json!(number.to_string());
+        }
+    }
+
+    json!({
+        "tool": {
+            "driver": driver,
+        },
+        "automationDetails": {
+            "id": format!("cargo-status/{}/", result.name.to_lowercase()),
+        },
+        "invocations": [{
+            "commandLine": result.command.join(" "),
+            "executionSuccessful": result.exit_code == Some(0),
+            "exitCode": result.exit_code,
+        }],
+        "results": results,
+    })
+}
+
+fn sarif_rule(id: &str, diagnostic: &Diagnostic) -> Value {
+    let mut rule = json!({
+        "id": id,
+        "defaultConfiguration": {
+            "level": diagnostic.level.as_str(),
+        },
+    });
+    if let Some(uri) = help_uri(id) {
+        rule["helpUri"] = json!(uri);
+    }
+    rule
+}
+
+fn sarif_result(diagnostic: &Diagnostic, rule_id: String, rule_index: usize) -> Value {
+    let mut result = json!({
+        "ruleId": rule_id,
+        "ruleIndex": rule_index,
+        "level": diagnostic.level.as_str(),
+        "message": {
+            "text": diagnostic.message,
+        },
+    });
+
+    if let Some(ref file) = diagnostic.file {
+        let mut region = json!({});
+        if let Some(line) = diagnostic.line {
+            region["startLine"] = json!(line);
+        }
+        if let Some(column) = diagnostic.column {
+            region["startColumn"] = json!(column);
+        }
+        if let Some(end_line) = diagnostic.end_line {
+            region["endLine"] = json!(end_line);
+        }
+        if let Some(end_column) = diagnostic.end_column {
+            region["endColumn"] = json!(end_column);
+        }
+
+        result["locations"] = json!([{
+            "physicalLocation": {
+                "artifactLocation": artifact_location(file),
+                "region": region,
+            }
+        }]);
+    }
+    if let Some(ref crate_name) = diagnostic.crate_name {
+        result["properties"] = json!({ "crate": crate_name });
+    }
+    result
+}
+
+/// Rule ID of a diagnostic: its lint name or error code, else its level
+fn rule_id(diagnostic: &Diagnostic) -> String {
+    diagnostic
+        .code
+        .clone()
+        .unwrap_or_else(|| diagnostic.level.as_str().to_string())
+}
+
+/// Documentation URL for a lint name or error code
+fn help_uri(rule_id: &str) -> Option<String> {
+    if let Some(lint) = rule_id.strip_prefix("clippy::") {
+        return Some(format!(
+            "https://rust-lang.github.io/rust-clippy/master/index.html#{}",
+            lint
+        ));
+    }
+    if let Some(lint) = rule_id.strip_prefix("rustdoc::") {
+        return Some(format!("https://doc.rust-lang.org/rustdoc/lints.html#{}", lint));
+    }
+
+    let is_error_code = rule_id.len() == 5
+        && rule_id.starts_with('E')
+        && rule_id[1..].chars().all(|c| c.is_ascii_digit());
+    if is_error_code {
+        return Some(format!("https://doc.rust-lang.org/error_codes/{}.html", rule_id));
+    }
+    None
+}
+
+/// Location of a file, relative to the source root unless the path is absolute
+fn artifact_location(file: &str) -> Value {
+    let uri = encode_uri_path(&file.replace('\\', "/"));
+    if uri.starts_with('/') {
+        json!({ "uri": format!("file://{}", uri) })
+    } else if Path::new(file).is_absolute() {
+        // Windows paths with a drive letter
+        json!({ "uri": format!("file:///{}", uri) })
+    } else {
+        json!({ "uri": uri, "uriBaseId": "%SRCROOT%" })
+    }
+}
+
+/// Percent-encodes characters that may not appear in a URI path
+fn encode_uri_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::DiagnosticLevel;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_render_sarif_report() {
+        let mut lint = Diagnostic::new(DiagnosticLevel::Warning, "this `if` has identical blocks");
+        lint.code = Some("clippy::if_same_then_else".to_string());
+        lint.file = Some("src/my lib.rs".to_string());
+        lint.line = Some(4);
+        lint.column = Some(5);
+        lint.end_line = Some(6);
+        lint.end_column = Some(10);
+        let mut mismatch = Diagnostic::new(DiagnosticLevel::Error, "mismatched types");
+        mismatch.code = Some("E0308".to_string());
+
+        let duration = Duration::from_secs(1);
+        let report = RunReport {
+            started_at: UNIX_EPOCH,
+            duration,
+            results: vec![
+                CheckResult {
+                    tool_version: Some("clippy 0.1.83 (90b35a6 2024-11-26)".to_string()),
+                    ..CheckResult::for_test("Clippy", CheckStatus::Warning { warnings: 2, duration })
+                        .with_diagnostics(vec![lint.clone(), lint])
+                },
+                CheckResult::for_test(
+                    "Build",
+                    CheckStatus::Error {
+                        errors: 1,
+                        warnings: 0,
+                        duration,
+                    },
+                )
+                .with_diagnostics(vec![mismatch]),
+                CheckResult::for_test("Test", CheckStatus::Success { warnings: 0, duration }),
+                CheckResult::for_test(
+                    "Doc",
+                    CheckStatus::Skipped {
+                        reason: "Check did not pass".to_string(),
+                    },
+                ),
+            ],
+            interrupted: false,
+            exit_code: 1,
+        };
+
+        let sarif: Value = serde_json::from_str(&render(&report).unwrap()).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        let runs = sarif["runs"].as_array().unwrap();
+        assert_eq!(runs.len(), 2);
+
+        let clippy = &runs[0];
+        assert_eq!(clippy["tool"]["driver"]["name"], "clippy");
+        assert_eq!(clippy["tool"]["driver"]["semanticVersion"], "0.1.83");
+        assert_eq!(clippy["automationDetails"]["id"], "cargo-status/clippy/");
+        assert_eq!(clippy["tool"]["driver"]["rules"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            clippy["tool"]["driver"]["rules"][0]["helpUri"],
+            "https://rust-lang.github.io/rust-clippy/master/index.html#if_same_then_else"
+        );
+        let location = &clippy["results"][1]["locations"][0]["physicalLocation"];
+        assert_eq!(clippy["results"][1]["ruleIndex"], 0);
+        assert_eq!(location["artifactLocation"]["uri"], "src/my%20lib.rs");
+        assert_eq!(location["region"]["endColumn"], 10);
+
+        let build = &runs[1];
+        assert_eq!(build["results"][0]["level"], "error");
+        assert_eq!(
+            build["tool"]["driver"]["rules"][0]["helpUri"],
+            "https://doc.rust-lang.org/error_codes/E0308.html"
+        );
+    }
+}
@@ -0,0 +1,516 @@
+//! Running the checks across many workspaces
+//!
+//! `--scan` finds every cargo workspace under the given directories (or the
+//! `[scan]` directories of the user config) and runs cargo-status in each one
+//! as a subprocess, with the same flags and a JSON report on stdout. A few
+//! workspaces are checked at a time; the reports are combined into a table
+//! with a row per workspace, and the exit code is the highest of all runs.
+
+use crate::config::{load_user_config, user_config_path};
+use crate::display::format_duration;
+use crate::error::{CargoStatusError, Result};
+use crate::tools::diagnostics::strip_ansi;
+use colored::*;
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tokio::task::JoinSet;
+
+/// Number of workspaces checked at the same time unless `--scan-jobs` is given
+pub const DEFAULT_SCAN_JOBS: usize = 2;
+
+/// How many directories deep workspaces are looked for
+const MAX_DEPTH: usize = 4;
+
+/// Directories that never hold a workspace worth checking
+const SKIPPED_DIRS: [&str; 2] = ["target", "node_modules"];
+
+/// Exit code of a workspace that was never checked because of Ctrl-C
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// Outcome of checking one workspace
+#[derive(Debug, Clone)]
+pub struct ScanResult {
+    pub dir: PathBuf,
+    /// Name shown in the table
+    pub label: String,
+    pub duration: Duration,
+    pub exit_code: i32,
+    pub outcome: ScanOutcome,
+}
+
+#[derive(Debug, Clone)]
+pub enum ScanOutcome {
+    /// The run's JSON report
+    Report(ScanReport),
+    /// cargo-status did not produce a report, e.g. because of a config error
+    Failed(String),
+    /// The workspace was not checked because the scan was interrupted
+    Cancelled,
+}
+
+/// The parts of a JSON report the combined table shows
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScanReport {
+    pub checks: Vec<ScanCheck>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScanCheck {
+    pub name: String,
+    pub status: String,
+    pub counts: ScanCounts,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScanCounts {
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl ScanCheck {
+    /// Short description of the check's status for a table cell
+    fn summary(&self) -> String {
+        match self.status.as_str() {
+            "success" => "✓".to_string(),
+            "warning" => plural(self.counts.warnings, "warning"),
+            "error" => plural(self.counts.errors, "error"),
+            "timed_out" => "timed out".to_string(),
+            status => status.to_string(),
+        }
+    }
+}
+
+/// Checks every workspace under `dirs` and prints the combined table
+///
+/// Without directories the `[scan]` directories of the user config are
+/// used. `args` are the command-line arguments of this run, passed on to
+/// every workspace's run. Returns the exit code of the scan.
+pub async fn run(dirs: &[PathBuf], jobs: Option<usize>, args: Vec<String>) -> Result<i32> {
+    let dirs = if dirs.is_empty() {
+        load_user_config()?.scan.dirs
+    } else {
+        dirs.to_vec()
+    };
+    if dirs.is_empty() {
+        let path = user_config_path().unwrap_or_else(|| PathBuf::from("config.toml"));
+        return Err(CargoStatusError::config(format!(
+            "No directories to scan; pass --scan <DIR> or list them under [scan] dirs in {}",
+            path.display()
+        )));
+    }
+
+    let workspaces = find_workspaces(&dirs)?;
+    let exe = env::current_exe().map_err(|source| CargoStatusError::Io {
+        context: "Failed to locate the cargo-status executable".to_string(),
+        source,
+    })?;
+    let args = child_args(args);
+    eprintln!("{}", format!("Checking {}...", plural(workspaces.len(), "workspace")).bold());
+
+    // Children get Ctrl-C themselves and report what they finished; the scan
+    // only stops starting new ones
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let signal_handle = {
+        let interrupted = interrupted.clone();
+        tokio::spawn(async move {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                interrupted.store(true, Ordering::SeqCst);
+            }
+        })
+    };
+
+    let max_running = jobs.unwrap_or(DEFAULT_SCAN_JOBS).max(1);
+    let mut pending = workspaces.into_iter();
+    let mut running = JoinSet::new();
+    let mut results = Vec::new();
+    loop {
+        while running.len() < max_running
+            && let Some((dir, label)) = pending.next()
+        {
+            if interrupted.load(Ordering::SeqCst) {
+                results.push(ScanResult {
+                    dir,
+                    label,
+                    duration: Duration::ZERO,
+                    exit_code: EXIT_INTERRUPTED,
+                    outcome: ScanOutcome::Cancelled,
+                });
+                continue;
+            }
+            let (exe, args) = (exe.clone(), args.clone());
+            running.spawn(async move { check_workspace(&exe, &args, dir, label).await });
+        }
+
+        let Some(joined) = running.join_next().await else {
+            break;
+        };
+        if let Ok(result) = joined {
+            print_progress(&result);
+            results.push(result);
+        }
+    }
+    signal_handle.abort();
+
+    results.sort_by(|a, b| a.dir.cmp(&b.dir));
+    print!("\n{}", render_table(&results));
+    Ok(exit_code(&results))
+}
+
+/// Workspace roots under `dirs`, with the name each is shown under
+///
+/// A directory with a `Cargo.toml` is a workspace root and is not searched
+/// further. Hidden directories and build output are skipped.
+pub fn find_workspaces(dirs: &[PathBuf]) -> Result<Vec<(PathBuf, String)>> {
+    let mut workspaces = Vec::new();
+    for dir in dirs {
+        if !dir.is_dir() {
+            return Err(CargoStatusError::WorkspaceScan(format!(
+                "{} is not a directory",
+                dir.display()
+            )));
+        }
+        let mut found = Vec::new();
+        find_in(dir, 0, &mut found);
+        for workspace in found {
+            let label = match workspace.strip_prefix(dir) {
+                Ok(relative) if !relative.as_os_str().is_empty() => relative.display().to_string(),
+                _ => workspace
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| workspace.display().to_string()),
+            };
+            workspaces.push((workspace, label));
+        }
+    }
+
+    workspaces.sort();
+    workspaces.dedup_by(|a, b| a.0 == b.0);
+    if workspaces.is_empty() {
+        let dirs: Vec<_> = dirs.iter().map(|dir| dir.display().to_string()).collect();
+        return Err(CargoStatusError::WorkspaceScan(format!(
+            "No cargo workspaces found under {}",
+            dirs.join(", ")
+        )));
+    }
+    Ok(workspaces)
+}
+
+fn find_in(dir: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+    if dir.join("Cargo.toml").is_file() {
+        found.push(dir.to_path_buf());
+        return;
+    }
+    if depth == MAX_DEPTH {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut subdirs: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str())
+        })
+        .map(|entry| entry.path())
+        .collect();
+    subdirs.sort();
+    for subdir in subdirs {
+        find_in(&subdir, depth + 1, found);
+    }
+}
+
+/// Options of the scan itself, with a value, that no workspace run gets
+///
+/// The outputs would replace or redirect the JSON report the scan reads back.
+const PARENT_OPTIONS: [&str; 6] = ["--scan-jobs", "--format", "--output", "--events", "--events-output", "--badge"];
+
+/// Arguments for each workspace's run: this run's, without the scan options
+/// and outputs, and with a JSON report on stdout
+pub fn child_args(args: Vec<String>) -> Vec<String> {
+    let mut child = Vec::new();
+    let mut args = args.into_iter().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        let name = arg.split_once('=').map_or(arg.as_str(), |(name, _)| name);
+        match name {
+            "--scan" if name == arg => {
+                while args.next_if(|value| !value.starts_with('-')).is_some() {}
+            }
+            // A workspace run that watches would never exit
+            "--scan" | "--watch" => {}
+            _ if PARENT_OPTIONS.contains(&name) => {
+                if name == arg {
+                    args.next();
+                }
+            }
+            _ => child.push(arg),
+        }
+    }
+    child.extend(["--format".to_string(), "json".to_string()]);
+    child
+}
+
+async fn check_workspace(exe: &Path, args: &[String], dir: PathBuf, label: String) -> ScanResult {
+    let started = Instant::now();
+    // The recursion guard is meant for cargo-status checking itself, not for
+    // the runs started here
+    let output = Command::new(exe)
+        .args(args)
+        .current_dir(&dir)
+        .env_remove("CARGO_STATUS_RUNNING")
+        .stdin(Stdio::null())
+        .output()
+        .await;
+
+    let (exit_code, outcome) = match output {
+        Ok(output) => {
+            let code = output.status.code().unwrap_or(1);
+            match serde_json::from_slice::<ScanReport>(&output.stdout) {
+                Ok(report) => (code, ScanOutcome::Report(report)),
+                Err(_) => {
+                    // Without checks to run, the help text goes to stdout
+                    let fallback = if output.status.success() { "no checks enabled" } else { "cargo-status failed" };
+                    let stderr = strip_ansi(&String::from_utf8_lossy(&output.stderr));
+                    let reason = stderr
+                        .lines()
+                        .rev()
+                        .find(|line| !line.trim().is_empty())
+                        .unwrap_or(fallback)
+                        .trim()
+                        .to_string();
+                    (code, ScanOutcome::Failed(reason))
+                }
+            }
+        }
+        Err(e) => (1, ScanOutcome::Failed(e.to_string())),
+    };
+
+    ScanResult {
+        dir,
+        label,
+        duration: started.elapsed(),
+        exit_code,
+        outcome,
+    }
+}
+
+fn print_progress(result: &ScanResult) {
+    let icon = match result.exit_code {
+        0 => "✓".green(),
+        _ => "✗".red(),
+    };
+    eprintln!("  {} {} ({})", icon, result.label, format_duration(result.duration));
+}
+
+/// Renders a colored table with a row per workspace and a column per check
+pub fn render_table(results: &[ScanResult]) -> String {
+    // Checks in the order they first appear
+    let mut columns: Vec<&str> = Vec::new();
+    for result in results {
+        if let ScanOutcome::Report(ref report) = result.outcome {
+            for check in &report.checks {
+                if !columns.contains(&check.name.as_str()) {
+                    columns.push(&check.name);
+                }
+            }
+        }
+    }
+
+    let rows: Vec<Vec<(String, &str)>> = results
+        .iter()
+        .map(|result| {
+            columns
+                .iter()
+                .map(|column| match result.outcome {
+                    ScanOutcome::Report(ref report) => report
+                        .checks
+                        .iter()
+                        .find(|check| check.name == *column)
+                        .map(|check| (check.summary(), check.status.as_str()))
+                        .unwrap_or_else(|| ("-".to_string(), "")),
+                    _ => ("-".to_string(), ""),
+                })
+                .collect()
+        })
+        .collect();
+
+    // Pad before coloring, so escape codes don't count towards the width
+    let label_width = results
+        .iter()
+        .map(|result| result.label.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Workspace".len());
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            rows.iter()
+                .map(|row| row[index].0.chars().count())
+                .chain([column.chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut table = format!("{:<label_width$}", "Workspace").bold().to_string();
+    for (column, width) in columns.iter().zip(&widths) {
+        table.push_str(&format!("  {:<width$}", column).bold().to_string());
+    }
+    table.push_str(&"  Time".bold().to_string());
+    table.push('\n');
+
+    for (result, row) in results.iter().zip(&rows) {
+        table.push_str(&format!("{:<label_width$}", result.label));
+        for ((text, status), width) in row.iter().zip(&widths) {
+            let padded = format!("  {:<width$}", text);
+            let colored = match *status {
+                "success" => padded.green(),
+                "warning" => padded.yellow(),
+                "error" | "failed" | "timed_out" => padded.red(),
+                _ => padded.dimmed(),
+            };
+            table.push_str(&colored.to_string());
+        }
+        table.push_str(&format!("  {}", format_duration(result.duration)));
+        match result.outcome {
+            ScanOutcome::Failed(ref reason) => {
+                table.push_str(&format!("  {}", reason).red().to_string());
+            }
+            ScanOutcome::Cancelled => table.push_str(&"  cancelled".dimmed().to_string()),
+            ScanOutcome::Report(_) => {}
+        }
+        table.push('\n');
+    }
+
+    let failed = results.iter().filter(|result| result.exit_code != 0).count();
+    let summary = format!(
+        "\n{} checked, {} passed, {} failed\n",
+        plural(results.len(), "workspace"),
+        results.len() - failed,
+        failed
+    );
+    table.push_str(&summary);
+    table
+}
+
+/// The highest exit code of all workspaces, so any failure fails the scan
+pub fn exit_code(results: &[ScanResult]) -> i32 {
+    results.iter().map(|result| result.exit_code).max().unwrap_or(0)
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_find_workspaces() {
+        let dir = TempDir::new().unwrap();
+        for workspace in ["api", "tools/cli", "tools/cli/crates/core", ".hidden/x", "web/target/pkg"] {
+            fs::create_dir_all(dir.path().join(workspace)).unwrap();
+            fs::write(dir.path().join(workspace).join("Cargo.toml"), "[package]\n").unwrap();
+        }
+        fs::create_dir_all(dir.path().join("docs")).unwrap();
+
+        let found = find_workspaces(&[dir.path().to_path_buf()]).unwrap();
+        let labels: Vec<_> = found.iter().map(|(_, label)| label.as_str()).collect();
+        assert_eq!(labels, vec!["api", "tools/cli"]);
+
+        let error = find_workspaces(&[dir.path().join("docs")]).unwrap_err();
+        assert!(matches!(error, CargoStatusError::WorkspaceScan(_)));
+    }
+
+    #[test]
+    fn test_child_args() {
+        let scan = args(&["cargo-status", "status", "--scan", "/a", "/b", "-c", "--scan-jobs", "4", "-t"]);
+        assert_eq!(child_args(scan), args(&["status", "-c", "-t", "--format", "json"]));
+        let scan = args(&["cargo-status", "status", "-l", "--scan=/a", "--scan"]);
+        assert_eq!(child_args(scan), args(&["status", "-l", "--format", "json"]));
+
+        let expected = args(&["status", "-c", "--format", "json"]);
+        for outputs in [
+            &["--format", "sarif"][..],
+            &["--format=html", "--output", "report.html"],
+            &["--output=report.json"],
+            &["--events", "ndjson"],
+            &["--events=ndjson", "--events-output", "events.ndjson"],
+            &["--events-output=events.ndjson"],
+            &["--badge", "status.svg"],
+            &["--badge=status.svg"],
+            &["--watch"],
+        ] {
+            let mut scan = args(&["cargo-status", "status", "--scan", "/a", "-c"]);
+            scan.extend(args(outputs));
+            assert_eq!(child_args(scan), expected, "{:?}", outputs);
+        }
+    }
+
+    #[test]
+    fn test_scan_rejects_outputs() {
+        use crate::config::cli::Cli;
+        use clap::Parser;
+
+        for flag in [&["--watch"][..], &["--format", "json"], &["--events", "ndjson"], &["--badge", "status.svg"]] {
+            let mut argv = vec!["cargo", "status", "--scan", "/a", "-c"];
+            argv.extend(flag);
+            assert!(Cli::try_parse_from(argv).is_err(), "{:?}", flag);
+        }
+    }
+
+    #[test]
+    fn test_render_table() {
+        let report = |statuses: &[(&str, &str, usize)]| {
+            ScanOutcome::Report(ScanReport {
+                checks: statuses
+                    .iter()
+                    .map(|(name, status, count)| ScanCheck {
+                        name: name.to_string(),
+                        status: status.to_string(),
+                        counts: ScanCounts {
+                            errors: *count,
+                            warnings: *count,
+                        },
+                    })
+                    .collect(),
+            })
+        };
+        let result = |label: &str, exit_code, outcome| ScanResult {
+            dir: PathBuf::from(label),
+            label: label.to_string(),
+            duration: Duration::from_millis(500),
+            exit_code,
+            outcome,
+        };
+        let results = vec![
+            result("api", 0, report(&[("Check", "success", 0), ("Clippy", "warning", 3)])),
+            result("web", 1, report(&[("Check", "error", 1), ("Test", "skipped", 0)])),
+            result("tools", 1, ScanOutcome::Failed("Configuration error: bad".to_string())),
+        ];
+
+        let table = strip_ansi(&render_table(&results));
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines[0], "Workspace  Check    Clippy      Test     Time");
+        assert_eq!(lines[1], "api        ✓        3 warnings  -        500ms");
+        assert_eq!(lines[2], "web        1 error  -           skipped  500ms");
+        assert_eq!(lines[3], "tools      -        -           -        500ms  Configuration error: bad");
+        assert_eq!(lines[5], "3 workspaces checked, 1 passed, 2 failed");
+        assert_eq!(exit_code(&results), 1);
+    }
+}
@@ -0,0 +1,312 @@
+//! Dependency-aware scheduling of status checks
+//!
+//! Checks may declare other checks they depend on. The scheduler starts every
+//! check whose dependencies have passed, runs independent checks concurrently
+//! and skips the dependents of checks that did not pass.
+
+use crate::display::CheckStatus;
+use crate::tools::{CheckResult, StatusCheck};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::time::Instant;
+use tokio::task::JoinSet;
+
+/// Runs a set of checks in dependency order
+pub struct Scheduler {
+    checks: Vec<StatusCheck>,
+    max_parallel: usize,
+}
+
+impl Scheduler {
+    /// Creates a scheduler that runs all ready checks at once
+    pub fn new(checks: Vec<StatusCheck>) -> Self {
+        Self {
+            checks,
+            max_parallel: usize::MAX,
+        }
+    }
+
+    /// Limits the number of checks running at the same time
+    pub fn max_parallel(mut self, max_parallel: usize) -> Self {
+        self.max_parallel = max_parallel.max(1);
+        self
+    }
+
+    /// Runs one check at a time, in the order the checks were given
+    pub fn sequential(mut self, sequential: bool) -> Self {
+        if sequential {
+            self.max_parallel = 1;
+        }
+        self
+    }
+
+    /// Runs every check and returns the results in completion order
+    ///
+    /// Dependencies on checks that are not part of this run are ignored.
+    pub async fn run(self) -> Vec<CheckResult> {
+        self.run_with(|check| async move { check.execute().await }).await
+    }
+
+    /// Like [`Self::run`], with `execute` running each check
+    async fn run_with<F, Fut>(self, execute: F) -> Vec<CheckResult>
+    where
+        F: Fn(StatusCheck) -> Fut,
+        Fut: Future<Output = CheckResult> + Send + 'static,
+    {
+        let scheduled: HashSet<String> = self.checks.iter().map(|c| c.name.clone()).collect();
+        let mut pending: Vec<StatusCheck> = self
+            .checks
+            .into_iter()
+            .map(|mut check| {
+                check.depends_on.retain(|dep| scheduled.contains(dep));
+                check
+            })
+            .collect();
+
+        // Name of each finished check mapped to whether it passed
+        let mut finished: HashMap<String, bool> = HashMap::new();
+        let mut results = Vec::new();
+        let mut running = JoinSet::new();
+        // Running checks by task, to report the ones whose task panics
+        let mut tasks = HashMap::new();
+
+        loop {
+            // Skip checks whose dependencies failed, repeating until no more
+            // skips cascade, then start every check that is ready
+            let mut index = 0;
+            while index < pending.len() {
+                let check = &pending[index];
+                if let Some(failed) = check
+                    .depends_on
+                    .iter()
+                    .find(|dep| finished.get(*dep) == Some(&false))
+                {
+                    let reason = format!("{} did not pass", failed);
+                    let check = pending.remove(index);
+                    let result = check.skip(reason);
+                    finished.insert(result.name.clone(), false);
+                    results.push(result);
+                    index = 0;
+                    continue;
+                }
+
+                let ready = check.depends_on.iter().all(|dep| finished.contains_key(dep));
+                if ready && running.len() < self.max_parallel {
+                    let check = pending.remove(index);
+                    let task = check.clone();
+                    let handle = running.spawn(execute(check));
+                    tasks.insert(handle.id(), (task, Instant::now()));
+                    continue;
+                }
+
+                index += 1;
+            }
+
+            let Some(joined) = running.join_next_with_id().await else {
+                // Anything still pending is waiting on itself
+                for check in pending.drain(..) {
+                    results.push(check.skip("dependency cycle".to_string()));
+                }
+                break;
+            };
+            let result = match joined {
+                Ok((id, result)) => {
+                    tasks.remove(&id);
+                    result
+                }
+                Err(e) => {
+                    let Some((check, started)) = tasks.remove(&e.id()) else {
+                        continue;
+                    };
+                    check.fail(format!("check panicked: {}", e), started.elapsed())
+                }
+            };
+            finished.insert(result.name.clone(), passed(&result.status));
+            results.push(result);
+        }
+
+        results
+    }
+}
+
+/// Whether dependents of a check that ran with this status may run
+///
+/// A check that skipped itself, e.g. because `--changed` found no packages
+/// for it, has nothing wrong to report, so its dependents still run. Checks
+/// skipped by the scheduler for a failed dependency never get here.
+fn passed(status: &CheckStatus) -> bool {
+    matches!(
+        status,
+        CheckStatus::Success { .. } | CheckStatus::Warning { .. } | CheckStatus::Skipped { .. }
+    )
+}
+
+/// Finds a dependency cycle, returning the path that closes it
+///
+/// `edges` maps each node to the nodes it depends on.
+pub fn find_cycle(edges: &HashMap<String, Vec<String>>) -> Option<Vec<String>> {
+    fn visit(
+        node: &str,
+        edges: &HashMap<String, Vec<String>>,
+        path: &mut Vec<String>,
+        done: &mut HashSet<String>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|visited| visited == node) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(node.to_string());
+            return Some(cycle);
+        }
+        if done.contains(node) {
+            return None;
+        }
+
+        path.push(node.to_string());
+        for dep in edges.get(node).into_iter().flatten() {
+            if let Some(cycle) = visit(dep, edges, path, done) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done.insert(node.to_string());
+        None
+    }
+
+    let mut nodes: Vec<&String> = edges.keys().collect();
+    nodes.sort();
+
+    let mut done = HashSet::new();
+    for node in nodes {
+        if let Some(cycle) = visit(node, edges, &mut Vec::new(), &mut done) {
+            return Some(cycle);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edges(pairs: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        pairs
+            .iter()
+            .map(|(node, deps)| {
+                (
+                    node.to_string(),
+                    deps.iter().map(|dep| dep.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let acyclic = edges(&[("test", &["build"]), ("doc", &["check"]), ("build", &[])]);
+        assert_eq!(find_cycle(&acyclic), None);
+
+        let cyclic = edges(&[("check", &["clippy"]), ("clippy", &["test"]), ("test", &["check"])]);
+        assert_eq!(
+            find_cycle(&cyclic),
+            Some(vec![
+                "check".to_string(),
+                "clippy".to_string(),
+                "test".to_string(),
+                "check".to_string()
+            ])
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_dependents_of_failed_checks_are_skipped() {
+        let shell = |script: &str| vec!["sh".to_string(), "-c".to_string(), script.to_string()];
+        let checks = vec![
+            StatusCheck::new("Build", shell("exit 1")),
+            StatusCheck::new("Test", shell("exit 0")).with_dependencies(vec!["Build".to_string()]),
+            StatusCheck::new("Doc", shell("exit 0")).with_dependencies(vec!["Test".to_string()]),
+            StatusCheck::new("Check", shell("exit 0")),
+        ];
+
+        let results = Scheduler::new(checks).run().await;
+        let status = |name: &str| {
+            results
+                .iter()
+                .find(|result| result.name == name)
+                .map(|result| result.status.clone())
+                .unwrap()
+        };
+
+        assert!(matches!(status("Build"), CheckStatus::Error { .. }));
+        assert!(matches!(status("Test"), CheckStatus::Skipped { .. }));
+        assert!(matches!(status("Doc"), CheckStatus::Skipped { .. }));
+        assert!(matches!(status("Check"), CheckStatus::Success { .. }));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_panicked_check_fails() {
+        let shell = |script: &str| vec!["sh".to_string(), "-c".to_string(), script.to_string()];
+        let checks = vec![
+            StatusCheck::new("Build", shell("exit 0")),
+            StatusCheck::new("Test", shell("exit 0")).with_dependencies(vec!["Build".to_string()]),
+        ];
+
+        let results = Scheduler::new(checks)
+            .run_with(|check| async move {
+                if check.name == "Build" {
+                    panic!("Build broke");
+                }
+                check.execute().await
+            })
+            .await;
+        let status = |name: &str| {
+            results
+                .iter()
+                .find(|result| result.name == name)
+                .map(|result| result.status.clone())
+                .unwrap()
+        };
+
+        assert_eq!(results.len(), 2);
+        assert!(matches!(status("Build"), CheckStatus::Failed { ref reason, .. } if reason.contains("panicked")));
+        assert_eq!(
+            status("Test"),
+            CheckStatus::Skipped {
+                reason: "Build did not pass".to_string()
+            }
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_dependents_of_unchanged_checks_run() {
+        let shell = |script: &str| vec!["sh".to_string(), "-c".to_string(), script.to_string()];
+        let checks = vec![
+            StatusCheck::new("Check", shell("exit 0"))
+                .with_skip_reason(Some("no packages changed since main".to_string())),
+            StatusCheck::new("Doc", shell("exit 0")).with_dependencies(vec!["Check".to_string()]),
+        ];
+
+        let results = Scheduler::new(checks).run().await;
+        assert!(matches!(results[0].status, CheckStatus::Skipped { .. }));
+        assert_eq!(results[1].name, "Doc");
+        assert!(matches!(results[1].status, CheckStatus::Success { .. }));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_max_parallel_limits_concurrency() {
+        let sleep = || vec!["sleep".to_string(), "0.2".to_string()];
+        let checks = vec![
+            StatusCheck::new("A", sleep()),
+            StatusCheck::new("B", sleep()),
+            StatusCheck::new("C", sleep()),
+        ];
+
+        let started = std::time::Instant::now();
+        let results = Scheduler::new(checks).max_parallel(1).run().await;
+
+        assert_eq!(results.len(), 3);
+        assert!(started.elapsed() >= std::time::Duration::from_millis(600));
+    }
+}
@@ -0,0 +1,431 @@
+//! Diagnostic parsing for cargo and rustc output
+//!
+//! Compiler-backed checks run with `--message-format=json-diagnostic-rendered-ansi`
+//! so that warnings and errors can be read from cargo's structured messages
+//! instead of being guessed from the rendered text. Tools without JSON output
+//! fall back to a line-anchored text parser.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Message format flag passed to compiler-backed cargo commands
+pub const JSON_MESSAGE_FORMAT: &str = "--message-format=json-diagnostic-rendered-ansi";
+
+/// Identity of a diagnostic: level, code, message, file, line and column
+pub(crate) type DedupKey = (
+    DiagnosticLevel,
+    Option<String>,
+    String,
+    Option<String>,
+    Option<usize>,
+    Option<usize>,
+);
+
+/// Severity of a diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticLevel {
+    Error,
+    Warning,
+}
+
+impl DiagnosticLevel {
+    /// Maps a rustc level string onto a countable level
+    ///
+    /// Notes, help messages and failure notes are not counted.
+    fn from_rustc(level: &str) -> Option<Self> {
+        if level.starts_with("error") {
+            Some(Self::Error)
+        } else if level == "warning" {
+            Some(Self::Warning)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the lowercase name used by rustc for this level
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+/// A single compiler or tool diagnostic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub level: DiagnosticLevel,
+    /// Error code or lint name (e.g. `E0308`, `clippy::needless_return`)
+    pub code: Option<String>,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// End of the primary span, when known (inclusive line, exclusive column)
+    pub end_line: Option<usize>,
+    pub end_column: Option<usize>,
+    /// Package the diagnostic was reported for
+    pub crate_name: Option<String>,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic without location information
+    pub fn new(level: DiagnosticLevel, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            code: None,
+            message: message.into(),
+            file: None,
+            line: None,
+            column: None,
+            end_line: None,
+            end_column: None,
+            crate_name: None,
+        }
+    }
+
+    /// Key used to drop duplicates reported once per target (e.g. lib and lib test)
+    pub(crate) fn dedup_key(&self) -> DedupKey {
+        (
+            self.level,
+            self.code.clone(),
+            self.message.clone(),
+            self.file.clone(),
+            self.line,
+            self.column,
+        )
+    }
+}
+
+/// A line of cargo's `--message-format=json` output
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    #[serde(default)]
+    package_id: Option<String>,
+    #[serde(default)]
+    message: Option<RustcDiagnostic>,
+}
+
+#[derive(Deserialize)]
+struct RustcDiagnostic {
+    message: String,
+    #[serde(default)]
+    code: Option<RustcCode>,
+    level: String,
+    #[serde(default)]
+    spans: Vec<RustcSpan>,
+    #[serde(default)]
+    rendered: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RustcCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct RustcSpan {
+    file_name: String,
+    line_start: usize,
+    #[serde(default)]
+    line_end: Option<usize>,
+    column_start: usize,
+    #[serde(default)]
+    column_end: Option<usize>,
+    is_primary: bool,
+}
+
+/// Collects diagnostics from tool output one line at a time
+#[derive(Debug, Clone)]
+pub struct DiagnosticCollector {
+    json: bool,
+    warning_patterns: Vec<String>,
+    diagnostics: Vec<Diagnostic>,
+    seen: HashSet<DedupKey>,
+    awaiting_location: bool,
+}
+
+impl DiagnosticCollector {
+    /// Creates a collector for the given output mode
+    ///
+    /// When `json` is false, lines starting with one of `warning_patterns`
+    /// (followed by `:` or `[`) are counted as warnings.
+    pub fn new(json: bool, warning_patterns: Vec<String>) -> Self {
+        Self {
+            json,
+            warning_patterns,
+            diagnostics: Vec::new(),
+            seen: HashSet::new(),
+            awaiting_location: false,
+        }
+    }
+
+    /// Feeds a single line of output to the collector
+    ///
+    /// Returns the human-readable text for the line, or `None` when the line
+    /// is a structured message with nothing to show (or a duplicate).
+    pub fn push_line(&mut self, line: &str) -> Option<String> {
+        if self.json
+            && line.starts_with('{')
+            && let Ok(message) = serde_json::from_str::<CargoMessage>(line)
+        {
+            return self.push_cargo_message(message);
+        }
+
+        self.push_text_line(line);
+        Some(line.to_string())
+    }
+
+    /// Returns the diagnostics collected so far
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Consumes the collector and returns all unique diagnostics
+    pub fn finish(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+
+    fn push_cargo_message(&mut self, message: CargoMessage) -> Option<String> {
+        if message.reason != "compiler-message" {
+            return None;
+        }
+        let diagnostic = message.message?;
+        let level = DiagnosticLevel::from_rustc(&diagnostic.level)?;
+        let primary = diagnostic
+            .spans
+            .iter()
+            .find(|span| span.is_primary)
+            .or_else(|| diagnostic.spans.first());
+
+        let parsed = Diagnostic {
+            level,
+            code: diagnostic.code.map(|code| code.code),
+            message: diagnostic.message,
+            file: primary.map(|span| span.file_name.clone()),
+            line: primary.map(|span| span.line_start),
+            column: primary.map(|span| span.column_start),
+            end_line: primary.and_then(|span| span.line_end),
+            end_column: primary.and_then(|span| span.column_end),
+            crate_name: message.package_id.as_deref().map(package_name_from_id),
+        };
+
+        if self.record(parsed) {
+            diagnostic.rendered.map(|text| text.trim_end().to_string())
+        } else {
+            None
+        }
+    }
+
+    fn push_text_line(&mut self, line: &str) {
+        let plain = strip_ansi(line);
+
+        if self.awaiting_location {
+            if let Some(location) = plain.trim_start().strip_prefix("--> ") {
+                self.attach_location(location);
+                return;
+            }
+            if !plain.trim().is_empty() {
+                self.awaiting_location = false;
+            }
+        }
+
+        let level = if is_diagnostic_line(&plain, "error") {
+            DiagnosticLevel::Error
+        } else if self
+            .warning_patterns
+            .iter()
+            .any(|pattern| is_diagnostic_line(&plain, pattern))
+        {
+            DiagnosticLevel::Warning
+        } else {
+            return;
+        };
+
+        if is_cargo_summary(&plain) {
+            return;
+        }
+
+        let (code, message) = split_code(&plain);
+        let mut diagnostic = Diagnostic::new(level, message);
+        diagnostic.code = code;
+        self.diagnostics.push(diagnostic);
+        self.awaiting_location = true;
+    }
+
+    /// Attaches a ` --> file:line:col` location to the last text diagnostic
+    fn attach_location(&mut self, location: &str) {
+        self.awaiting_location = false;
+        let Some(mut diagnostic) = self.diagnostics.pop() else {
+            return;
+        };
+
+        let mut parts = location.trim().rsplitn(3, ':');
+        let column = parts.next().and_then(|c| c.parse().ok());
+        let line = parts.next().and_then(|l| l.parse().ok());
+        if let (Some(file), Some(line), Some(column)) = (parts.next(), line, column) {
+            diagnostic.file = Some(file.to_string());
+            diagnostic.line = Some(line);
+            diagnostic.column = Some(column);
+        }
+
+        // Text diagnostics are deduplicated once their location is known
+        if self.seen.insert(diagnostic.dedup_key()) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    /// Records a diagnostic, returning false if it was already seen
+    fn record(&mut self, diagnostic: Diagnostic) -> bool {
+        if self.seen.insert(diagnostic.dedup_key()) {
+            self.diagnostics.push(diagnostic);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Counts errors and warnings in a list of diagnostics
+pub fn count_diagnostics(diagnostics: &[Diagnostic]) -> (usize, usize) {
+    diagnostics
+        .iter()
+        .fold((0, 0), |(errors, warnings), diagnostic| match diagnostic.level {
+            DiagnosticLevel::Error => (errors + 1, warnings),
+            DiagnosticLevel::Warning => (errors, warnings + 1),
+        })
+}
+
+/// Checks whether a line is a rustc-style `<prefix>:` or `<prefix>[CODE]:` header
+fn is_diagnostic_line(line: &str, prefix: &str) -> bool {
+    line.strip_prefix(prefix)
+        .is_some_and(|rest| rest.starts_with(':') || rest.starts_with('['))
+}
+
+/// Cargo and rustc summary lines that repeat counts already reported
+fn is_cargo_summary(line: &str) -> bool {
+    const SUMMARIES: [&str; 5] = [
+        "error: could not compile",
+        "error: aborting due to",
+        "warning: build failed, waiting for other jobs",
+        "error: test failed, to rerun pass",
+        "error: doctest failed, to rerun pass",
+    ];
+
+    SUMMARIES.iter().any(|summary| line.starts_with(summary))
+        || (line.starts_with("warning: ")
+            && (line.contains(" generated ") && line.contains(" warning")
+                || line.ends_with(" warning emitted")
+                || line.ends_with(" warnings emitted")))
+}
+
+/// Splits `error[E0308]: message` into its code and message
+fn split_code(line: &str) -> (Option<String>, String) {
+    let (header, message) = line.split_once(':').unwrap_or((line, ""));
+    let code = header
+        .split_once('[')
+        .and_then(|(_, rest)| rest.strip_suffix(']'))
+        .map(str::to_string);
+    (code, message.trim().to_string())
+}
+
+/// Extracts the package name from a cargo package ID
+///
+/// Handles both the current spec format (`path+file:///dir#name@1.0.0`,
+/// `path+file:///name#1.0.0`) and the legacy `name 1.0.0 (source)` format.
+pub fn package_name_from_id(package_id: &str) -> String {
+    if let Some((source, fragment)) = package_id.rsplit_once('#') {
+        if let Some((name, _version)) = fragment.split_once('@') {
+            return name.to_string();
+        }
+        let path = source.trim_end_matches('/');
+        return path.rsplit('/').next().unwrap_or(path).to_string();
+    }
+
+    package_id
+        .split_whitespace()
+        .next()
+        .unwrap_or(package_id)
+        .to_string()
+}
+
+/// Removes ANSI escape sequences from a string
+pub fn strip_ansi(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            if chars.peek() == Some(&'[') {
+                chars.next();
+                // Skip parameters until the final byte of the CSI sequence
+                for next in chars.by_ref() {
+                    if ('@'..='~').contains(&next) {
+                        break;
+                    }
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WARNING_JSON: &str = r#"{"reason":"compiler-message","package_id":"path+file:///tmp/demo#0.1.0","message":{"message":"unused variable: `x`","code":{"code":"unused_variables","explanation":null},"level":"warning","spans":[{"file_name":"src/lib.rs","line_start":1,"column_start":18,"is_primary":true}],"rendered":"warning: unused variable: `x`\n"}}"#;
+
+    #[test]
+    fn test_json_diagnostics_are_deduplicated() {
+        let mut collector = DiagnosticCollector::new(true, vec!["warning".to_string()]);
+        assert_eq!(
+            collector.push_line(WARNING_JSON),
+            Some("warning: unused variable: `x`".to_string())
+        );
+        assert_eq!(collector.push_line(WARNING_JSON), None);
+        assert_eq!(collector.push_line(r#"{"reason":"build-finished","success":true}"#), None);
+
+        let diagnostics = collector.finish();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("unused_variables"));
+        assert_eq!(diagnostics[0].file.as_deref(), Some("src/lib.rs"));
+        assert_eq!(diagnostics[0].line, Some(1));
+        assert_eq!(diagnostics[0].crate_name.as_deref(), Some("demo"));
+    }
+
+    #[test]
+    fn test_text_parsing_ignores_incidental_matches() {
+        let output = "\
+test tests::handles_warning ... ok
+/// error: in a doc comment
+\u{1b}[1m\u{1b}[33mwarning\u{1b}[0m: unused import
+  --> src/main.rs:3:5
+error[E0425]: cannot find value `y`
+warning: `demo` (bin \"demo\") generated 1 warning
+error: could not compile `demo` (bin \"demo\") due to 1 previous error";
+
+        let mut collector = DiagnosticCollector::new(false, vec!["warning".to_string()]);
+        for line in output.lines() {
+            collector.push_line(line);
+        }
+
+        let diagnostics = collector.finish();
+        assert_eq!(count_diagnostics(&diagnostics), (1, 1));
+        assert_eq!(diagnostics[0].file.as_deref(), Some("src/main.rs"));
+        assert_eq!(diagnostics[0].line, Some(3));
+        assert_eq!(diagnostics[1].code.as_deref(), Some("E0425"));
+    }
+
+    #[test]
+    fn test_package_name_from_id() {
+        assert_eq!(package_name_from_id("path+file:///work/foo#bar@0.1.0"), "bar");
+        assert_eq!(package_name_from_id("path+file:///work/foo#0.1.0"), "foo");
+        assert_eq!(package_name_from_id("foo 0.1.0 (path+file:///work/foo)"), "foo");
+    }
+}
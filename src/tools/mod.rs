@@ -3,9 +3,13 @@
 //! This module provides a unified interface for running different cargo tools.
 //! Each tool is implemented as a separate module with common patterns.
 
+pub mod diagnostics;
 pub mod registry;
 pub mod status_check;
+pub mod test_results;
 
 // Re-export commonly used types
+pub use diagnostics::{Diagnostic, DiagnosticLevel};
 pub use registry::create_all_checks;
-pub use status_check::StatusCheck;
+pub use status_check::{CheckResult, StatusCheck, TestCounts};
+pub use test_results::{TestCase, TestOutcome};
@@ -1,53 +1,182 @@
 //! Tool registry for managing available cargo tools
 
-use crate::cache::has_tool_cached;
-use crate::config::{build_command_with_config, Config};
+use crate::cache::tools::{ToolInfo, ToolSpec};
+use crate::cache::{detect_tool, probe_tool};
+use crate::config::{build_command_with_config, Config, TargetDirStrategy};
 use crate::display::StatusEvent;
 use crate::tools::status_check::StatusCheck;
-use std::process::{Command, Stdio};
+use crate::tools::test_results::{nextest_junit_path, NEXTEST_JUNIT_CONFIG};
+use crate::workspace::Workspace;
+use std::env;
+use std::path::{Path, PathBuf};
 use tokio::sync::mpsc;
 
+/// rustc, which compiles for the Check, Build, Doc and Test checks
+pub const RUSTC: ToolSpec = ToolSpec {
+    name: "rustc",
+    command: &["rustc", "--version"],
+    binary: Some("rustc"),
+};
+
+pub const RUSTFMT: ToolSpec = ToolSpec {
+    name: "fmt",
+    command: &["cargo", "fmt", "--version"],
+    binary: Some("cargo-fmt"),
+};
+
+pub const CLIPPY: ToolSpec = ToolSpec {
+    name: "clippy",
+    command: &["cargo", "clippy", "--version"],
+    binary: Some("cargo-clippy"),
+};
+
+pub const AUDIT: ToolSpec = ToolSpec {
+    name: "audit",
+    command: &["cargo", "audit", "--version"],
+    binary: Some("cargo-audit"),
+};
+
+pub const NEXTEST: ToolSpec = ToolSpec {
+    name: "nextest",
+    command: &["cargo", "nextest", "--version"],
+    binary: Some("cargo-nextest"),
+};
+
 /// Registry for managing available tools and creating checks
 pub struct ToolRegistry;
 
 impl ToolRegistry {
     /// Check if clippy is available
     pub fn has_clippy() -> bool {
-        has_tool_cached("clippy", || {
-            Command::new("cargo")
-                .args(["clippy", "--version"])
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .status()
-                .map(|status| status.success())
-                .unwrap_or(false)
-        })
+        detect_tool(&CLIPPY).available
     }
 
     /// Check if cargo-audit is available
     pub fn has_audit() -> bool {
-        has_tool_cached("audit", || {
-            Command::new("cargo")
-                .args(["audit", "--version"])
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .status()
-                .map(|status| status.success())
-                .unwrap_or(false)
-        })
+        detect_tool(&AUDIT).available
     }
 
     /// Check if cargo-nextest is available
     pub fn has_nextest() -> bool {
-        has_tool_cached("nextest", || {
-            Command::new("cargo")
-                .args(["nextest", "--version"])
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .status()
-                .map(|status| status.success())
-                .unwrap_or(false)
-        })
+        detect_tool(&NEXTEST).available
+    }
+
+    /// Version reported by a tool, e.g. `clippy 0.1.83 (90b35a6 2024-11-26)`
+    pub fn tool_version(spec: &ToolSpec) -> Option<String> {
+        detect_tool(spec).version
+    }
+
+    /// Directory for cargo-status' own files, `<target>/cargo-status`
+    ///
+    /// The target directory is the one `cargo metadata` reports for the
+    /// workspace, so `build.target-dir` in `.cargo/config.toml` is honoured.
+    /// Without the metadata it is `$CARGO_TARGET_DIR` or `target`.
+    pub fn status_dir(workspace: Option<&Workspace>) -> PathBuf {
+        Self::cargo_target_dir(workspace).join("cargo-status")
+    }
+
+    /// Cargo's own target directory for the workspace
+    fn cargo_target_dir(workspace: Option<&Workspace>) -> PathBuf {
+        match workspace {
+            Some(workspace) => workspace.target_dir.clone(),
+            None => env::var_os("CARGO_TARGET_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("target")),
+        }
+    }
+
+    /// Get the target directory for a compiling check under the configured strategy
+    ///
+    /// Returns `None` when the check should use cargo's default target directory.
+    pub fn target_dir(config: &Config, tool: &str, command: &[String]) -> Option<PathBuf> {
+        let base = Self::status_dir(config.workspace.as_ref());
+
+        match config.target_dir_strategy() {
+            TargetDirStrategy::Shared => None,
+            TargetDirStrategy::PerCheck => Some(base.join(tool)),
+            TargetDirStrategy::Profile => Some(base.join(cargo_profile(command))),
+        }
+    }
+
+    /// Packages a compiling check covers: `-p` for each package selected by
+    /// `--changed`, otherwise those of [`Self::member_args`]
+    fn package_args(config: &Config) -> Vec<String> {
+        match config.packages {
+            Some(ref packages) => packages
+                .iter()
+                .flat_map(|package| ["-p".to_string(), package.clone()])
+                .collect(),
+            None => Self::member_args(config),
+        }
+    }
+
+    /// `-p` for the member `--manifest-path` points at, or the whole workspace
+    fn member_args(config: &Config) -> Vec<String> {
+        match config.package {
+            Some(ref package) => vec!["-p".to_string(), package.clone()],
+            None => vec!["--workspace".to_string()],
+        }
+    }
+
+    /// `--manifest-path` for cargo commands, if one was given or found
+    fn manifest_args(config: &Config) -> Vec<String> {
+        match config.args.manifest_path {
+            Some(ref path) => vec!["--manifest-path".to_string(), path.display().to_string()],
+            None => Vec::new(),
+        }
+    }
+
+    /// Why a compiling check has nothing to do, if `--changed` selected no packages
+    fn nothing_changed(config: &Config) -> Option<String> {
+        let base = config.args.changed.as_deref()?;
+        config
+            .packages
+            .as_ref()
+            .filter(|packages| packages.is_empty())
+            .map(|_| format!("no packages changed since {}", base))
+    }
+
+    /// Enable nextest's JUnit output through a tool config file
+    ///
+    /// Returns the tool config file to write before the check runs and the
+    /// path nextest will write the JUnit file to, or `None` without a current
+    /// directory.
+    fn configure_nextest_junit(
+        cmd: &mut Vec<String>,
+        workspace: Option<&Workspace>,
+        target_dir: Option<&Path>,
+    ) -> Option<(PathBuf, PathBuf)> {
+        let root = env::current_dir().ok()?;
+        let default_target = root.join(Self::cargo_target_dir(workspace));
+        let config_path = default_target.join("cargo-status").join("nextest.toml");
+
+        // Insert after `nextest run`, ahead of any `--` in user arguments
+        cmd.insert(3, format!("--tool-config-file=cargo-status:{}", config_path.display()));
+
+        let profile = nextest_profile(cmd);
+        let junit_path = nextest_junit_path(&root, target_dir.unwrap_or(&default_target), &profile);
+        Some((config_path, junit_path))
+    }
+
+    /// Detection of a tool the checks of `config` use
+    ///
+    /// When the checks are only listed, nothing is stored and no version is
+    /// asked for.
+    fn detect(config: &Config, spec: &ToolSpec) -> ToolInfo {
+        if config.dry_run {
+            probe_tool(spec)
+        } else {
+            detect_tool(spec)
+        }
+    }
+
+    /// Version of a tool the checks of `config` use, see [`Self::detect`]
+    fn version(config: &Config, spec: &ToolSpec) -> Option<String> {
+        if config.dry_run {
+            None
+        } else {
+            Self::tool_version(spec)
+        }
     }
 
     /// Create a StatusCheck for the format tool
@@ -55,7 +184,8 @@ impl ToolRegistry {
         config: &Config,
         event_sender: mpsc::UnboundedSender<StatusEvent>,
     ) -> StatusCheck {
-        let base_cmd = vec!["cargo".to_string(), "fmt".to_string(), "--all".to_string()];
+        let mut base_cmd = vec!["cargo".to_string(), "fmt".to_string(), "--all".to_string()];
+        base_cmd.extend(Self::manifest_args(config));
         let fmt_cmd = if let Some(ref toml_config) = config.toml_config {
             build_command_with_config(base_cmd, &toml_config.tool_args.fmt)
         } else {
@@ -63,7 +193,10 @@ impl ToolRegistry {
         };
 
         StatusCheck::new("Format", fmt_cmd)
+            .with_tool_version(Self::version(config, &RUSTFMT))
             .with_verbose(config.is_tool_verbose("fmt"))
+            .with_timeout(config.tool_timeout("fmt"))
+            .with_dependencies(config.check_dependencies("fmt"))
             .with_event_sender(event_sender)
     }
 
@@ -72,20 +205,26 @@ impl ToolRegistry {
         config: &Config,
         event_sender: mpsc::UnboundedSender<StatusEvent>,
     ) -> StatusCheck {
-        let base_cmd = vec![
-            "cargo".to_string(),
-            "check".to_string(),
-            "--workspace".to_string(),
-            "--all-targets".to_string(),
-        ];
+        let mut base_cmd = vec!["cargo".to_string(), "check".to_string()];
+        base_cmd.extend(Self::manifest_args(config));
+        base_cmd.extend(Self::package_args(config));
+        base_cmd.push("--all-targets".to_string());
         let check_cmd = if let Some(ref toml_config) = config.toml_config {
             build_command_with_config(base_cmd, &toml_config.tool_args.check)
         } else {
             base_cmd
         };
 
+        let target_dir = Self::target_dir(config, "check", &check_cmd);
+
         StatusCheck::new("Check", check_cmd)
+            .with_tool_version(Self::version(config, &RUSTC))
+            .with_skip_reason(Self::nothing_changed(config))
+            .with_target_dir(target_dir)
+            .with_json_diagnostics(true)
             .with_verbose(config.is_tool_verbose("check"))
+            .with_timeout(config.tool_timeout("check"))
+            .with_dependencies(config.check_dependencies("check"))
             .with_event_sender(event_sender)
     }
 
@@ -94,19 +233,17 @@ impl ToolRegistry {
         config: &Config,
         event_sender: mpsc::UnboundedSender<StatusEvent>,
     ) -> Option<StatusCheck> {
-        if !Self::has_clippy() {
+        if !Self::detect(config, &CLIPPY).available {
             eprintln!("Warning: clippy is not installed. Skipping clippy check.");
             eprintln!("Install it with: rustup component add clippy");
             return None;
         }
 
-        let mut base_cmd = vec![
-            "cargo".to_string(),
-            "clippy".to_string(),
-            "--workspace".to_string(),
-            "--all-targets".to_string(),
-            "--all-features".to_string(),
-        ];
+        let mut base_cmd = vec!["cargo".to_string(), "clippy".to_string()];
+        base_cmd.extend(Self::manifest_args(config));
+        base_cmd.extend(Self::package_args(config));
+        base_cmd.push("--all-targets".to_string());
+        base_cmd.push("--all-features".to_string());
 
         // Add custom clippy arguments from command line before the -- separator
         if let Some(ref clippy_args) = config.args.clippy_args {
@@ -125,10 +262,17 @@ impl ToolRegistry {
         base_cmd.push("-D".to_string());
         base_cmd.push("warnings".to_string());
 
+        let target_dir = Self::target_dir(config, "clippy", &base_cmd);
+
         Some(
             StatusCheck::new("Clippy", base_cmd)
-                .with_warning_patterns(vec!["warning".to_string(), "help:".to_string()])
+                .with_tool_version(Self::version(config, &CLIPPY))
+                .with_skip_reason(Self::nothing_changed(config))
+                .with_target_dir(target_dir)
+                .with_json_diagnostics(true)
                 .with_verbose(config.is_tool_verbose("clippy"))
+                .with_timeout(config.tool_timeout("clippy"))
+                .with_dependencies(config.check_dependencies("clippy"))
                 .with_event_sender(event_sender),
         )
     }
@@ -138,7 +282,8 @@ impl ToolRegistry {
         config: &Config,
         event_sender: mpsc::UnboundedSender<StatusEvent>,
     ) -> StatusCheck {
-        let mut test_cmd = if Self::has_nextest() {
+        let nextest = Self::detect(config, &NEXTEST).available;
+        let mut test_cmd = if nextest {
             vec![
                 "cargo".to_string(),
                 "nextest".to_string(),
@@ -147,12 +292,13 @@ impl ToolRegistry {
                 "--color=always".to_string(),
             ]
         } else {
-            vec![
-                "cargo".to_string(),
-                "test".to_string(),
-                "--workspace".to_string(),
-            ]
+            vec!["cargo".to_string(), "test".to_string()]
         };
+        test_cmd.extend(Self::manifest_args(config));
+        // nextest covers the workspace's default members unless told otherwise
+        if !nextest || config.packages.is_some() || config.package.is_some() {
+            test_cmd.extend(Self::package_args(config));
+        }
 
         // Add custom test arguments from command line
         if let Some(ref test_args) = config.args.test_args {
@@ -166,8 +312,27 @@ impl ToolRegistry {
             test_cmd.extend(toml_config.tool_args.test.iter().cloned());
         }
 
-        StatusCheck::new("Test", test_cmd)
+        let target_dir = Self::target_dir(config, "test", &test_cmd);
+
+        // Have nextest write JUnit results that can be read back per test
+        let junit = if nextest {
+            Self::configure_nextest_junit(&mut test_cmd, config.workspace.as_ref(), target_dir.as_deref())
+        } else {
+            None
+        };
+
+        let mut check = StatusCheck::new("Test", test_cmd);
+        if let Some((ref config_path, _)) = junit {
+            check = check.with_config_file(config_path.clone(), NEXTEST_JUNIT_CONFIG);
+        }
+        check
+            .with_tool_version(Self::version(config, if nextest { &NEXTEST } else { &RUSTC }))
+            .with_skip_reason(Self::nothing_changed(config))
+            .with_target_dir(target_dir)
+            .with_junit_path(junit.map(|(_, junit_path)| junit_path))
             .with_verbose(config.is_tool_verbose("test"))
+            .with_timeout(config.tool_timeout("test"))
+            .with_dependencies(config.check_dependencies("test"))
             .with_event_sender(event_sender)
     }
 
@@ -176,12 +341,10 @@ impl ToolRegistry {
         config: &Config,
         event_sender: mpsc::UnboundedSender<StatusEvent>,
     ) -> StatusCheck {
-        let mut build_cmd = vec![
-            "cargo".to_string(),
-            "build".to_string(),
-            "--workspace".to_string(),
-            "--all-targets".to_string(),
-        ];
+        let mut build_cmd = vec!["cargo".to_string(), "build".to_string()];
+        build_cmd.extend(Self::manifest_args(config));
+        build_cmd.extend(Self::package_args(config));
+        build_cmd.push("--all-targets".to_string());
 
         // Add custom build arguments from command line
         if let Some(ref build_args) = config.args.build_args {
@@ -195,8 +358,16 @@ impl ToolRegistry {
             build_cmd.extend(toml_config.tool_args.build.iter().cloned());
         }
 
+        let target_dir = Self::target_dir(config, "build", &build_cmd);
+
         StatusCheck::new("Build", build_cmd)
+            .with_tool_version(Self::version(config, &RUSTC))
+            .with_skip_reason(Self::nothing_changed(config))
+            .with_target_dir(target_dir)
+            .with_json_diagnostics(true)
             .with_verbose(config.is_tool_verbose("build"))
+            .with_timeout(config.tool_timeout("build"))
+            .with_dependencies(config.check_dependencies("build"))
             .with_event_sender(event_sender)
     }
 
@@ -205,20 +376,25 @@ impl ToolRegistry {
         config: &Config,
         event_sender: mpsc::UnboundedSender<StatusEvent>,
     ) -> StatusCheck {
-        let base_cmd = vec![
-            "cargo".to_string(),
-            "doc".to_string(),
-            "--workspace".to_string(),
-            "--no-deps".to_string(),
-        ];
+        let mut base_cmd = vec!["cargo".to_string(), "doc".to_string()];
+        base_cmd.extend(Self::member_args(config));
+        base_cmd.push("--no-deps".to_string());
+        base_cmd.extend(Self::manifest_args(config));
         let doc_cmd = if let Some(ref toml_config) = config.toml_config {
             build_command_with_config(base_cmd, &toml_config.tool_args.doc)
         } else {
             base_cmd
         };
 
+        let target_dir = Self::target_dir(config, "doc", &doc_cmd);
+
         StatusCheck::new("Doc", doc_cmd)
+            .with_tool_version(Self::version(config, &RUSTC))
+            .with_target_dir(target_dir)
+            .with_json_diagnostics(true)
             .with_verbose(config.is_tool_verbose("doc"))
+            .with_timeout(config.tool_timeout("doc"))
+            .with_dependencies(config.check_dependencies("doc"))
             .with_event_sender(event_sender)
     }
 
@@ -227,13 +403,21 @@ impl ToolRegistry {
         config: &Config,
         event_sender: mpsc::UnboundedSender<StatusEvent>,
     ) -> Option<StatusCheck> {
-        if !Self::has_audit() {
+        if !Self::detect(config, &AUDIT).available {
             eprintln!("Warning: cargo-audit is not installed. Skipping audit check.");
             eprintln!("Install it with: cargo install cargo-audit");
             return None;
         }
 
-        let base_cmd = vec!["cargo".to_string(), "audit".to_string()];
+        // cargo-audit reads the lockfile rather than the manifest, and a
+        // member shares the workspace's
+        let mut base_cmd = vec!["cargo".to_string(), "audit".to_string()];
+        if config.args.manifest_path.is_some()
+            && let Ok(root) = env::current_dir()
+        {
+            base_cmd.push("--file".to_string());
+            base_cmd.push(root.join("Cargo.lock").display().to_string());
+        }
         let audit_cmd = if let Some(ref toml_config) = config.toml_config {
             build_command_with_config(base_cmd, &toml_config.tool_args.audit)
         } else {
@@ -242,12 +426,48 @@ impl ToolRegistry {
 
         Some(
             StatusCheck::new("Audit", audit_cmd)
+                .with_tool_version(Self::version(config, &AUDIT))
                 .with_verbose(config.is_tool_verbose("audit"))
+                .with_timeout(config.tool_timeout("audit"))
+                .with_dependencies(config.check_dependencies("audit"))
                 .with_event_sender(event_sender),
         )
     }
 }
 
+/// Get the cargo profile a command builds with (`dev` unless overridden)
+fn cargo_profile(command: &[String]) -> &str {
+    let mut args = command.iter().take_while(|arg| *arg != "--");
+    while let Some(arg) = args.next() {
+        if arg == "--release" || arg == "-r" {
+            return "release";
+        }
+        if arg == "--profile" {
+            if let Some(profile) = args.next() {
+                return profile;
+            }
+        } else if let Some(profile) = arg.strip_prefix("--profile=") {
+            return profile;
+        }
+    }
+    "dev"
+}
+
+/// Get the nextest profile a command runs with (`default` unless overridden)
+fn nextest_profile(command: &[String]) -> String {
+    let mut args = command.iter().take_while(|arg| *arg != "--");
+    while let Some(arg) = args.next() {
+        if arg == "--profile" || arg == "-P" {
+            if let Some(profile) = args.next() {
+                return profile.clone();
+            }
+        } else if let Some(profile) = arg.strip_prefix("--profile=") {
+            return profile.to_string();
+        }
+    }
+    env::var("NEXTEST_PROFILE").unwrap_or_else(|_| "default".to_string())
+}
+
 /// Create all enabled checks based on configuration
 pub fn create_all_checks(
     config: &Config,
@@ -294,16 +514,11 @@ pub fn create_all_checks(
 mod tests {
     use super::*;
     use crate::config::{Config, StatusArgs};
-    use std::collections::HashSet;
 
     #[test]
     fn test_create_checks_empty() {
         // Create config without TOML defaults by passing explicit false values
-        let config = Config {
-            args: StatusArgs::default(),
-            toml_config: None,
-            verbose_tools: HashSet::new(),
-        };
+        let config = Config::for_args(StatusArgs::default());
         let (sender, _receiver) = mpsc::unbounded_channel();
         let checks = create_all_checks(&config, sender);
         assert!(checks.is_empty());
@@ -321,4 +536,80 @@ mod tests {
         assert_eq!(checks.len(), 1);
         assert_eq!(checks[0].name, "Format");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_dry_run_checks() {
+        let config = Config {
+            dry_run: true,
+            ..Config::for_args(StatusArgs {
+                fmt: true,
+                test: true,
+                ..Default::default()
+            })
+        };
+        let (sender, _receiver) = mpsc::unbounded_channel();
+        let checks = create_all_checks(&config, sender);
+        assert_eq!(checks.len(), 2);
+        // Listing the checks asks no tool for its version
+        assert!(checks.iter().all(|check| check.tool_version.is_none()));
+    }
+
+    #[test]
+    fn test_target_dir_strategy() {
+        let mut config = Config::for_args(StatusArgs::default());
+        let build = vec![
+            "cargo".to_string(),
+            "build".to_string(),
+            "--release".to_string(),
+        ];
+        assert_eq!(ToolRegistry::target_dir(&config, "build", &build), None);
+
+        config.args.target_dir_strategy = Some(TargetDirStrategy::PerCheck);
+        let per_check = ToolRegistry::target_dir(&config, "build", &build).unwrap();
+        assert!(per_check.ends_with("cargo-status/build"));
+
+        config.args.target_dir_strategy = Some(TargetDirStrategy::Profile);
+        let profile = ToolRegistry::target_dir(&config, "build", &build).unwrap();
+        assert!(profile.ends_with("cargo-status/release"));
+    }
+
+    #[test]
+    fn test_changed_packages_narrow_commands() {
+        let mut config = Config::for_args(StatusArgs::default());
+        let (sender, _receiver) = mpsc::unbounded_channel();
+        let check = ToolRegistry::create_check_check(&config, sender.clone());
+        assert_eq!(check.command[..3], ["cargo", "check", "--workspace"]);
+        assert_eq!(check.skip_reason, None);
+
+        config.args.changed = Some("main".to_string());
+        config.packages = Some(["api".to_string(), "core".to_string()].into());
+        let build = ToolRegistry::create_build_check(&config, sender.clone());
+        assert_eq!(build.command[2..6], ["-p", "api", "-p", "core"]);
+
+        config.packages = Some(Default::default());
+        let check = ToolRegistry::create_check_check(&config, sender);
+        assert_eq!(check.skip_reason.as_deref(), Some("no packages changed since main"));
+    }
+
+    #[test]
+    fn test_manifest_path_passed_to_cargo() {
+        let mut config = Config::for_args(StatusArgs::default());
+        config.args.manifest_path = Some(PathBuf::from("/ws/crates/api/Cargo.toml"));
+        let (sender, _receiver) = mpsc::unbounded_channel();
+        let manifest = ["--manifest-path", "/ws/crates/api/Cargo.toml"];
+
+        let fmt = ToolRegistry::create_fmt_check(&config, sender.clone());
+        assert_eq!(fmt.command[3..5], manifest);
+        let check = ToolRegistry::create_check_check(&config, sender.clone());
+        assert_eq!(check.command[2..5], [manifest[0], manifest[1], "--workspace"]);
+        let doc = ToolRegistry::create_doc_check(&config, sender.clone());
+        assert_eq!(doc.command[4..6], manifest);
+
+        // A member is checked on its own
+        config.package = Some("api".to_string());
+        let check = ToolRegistry::create_check_check(&config, sender.clone());
+        assert_eq!(check.command[2..6], [manifest[0], manifest[1], "-p", "api"]);
+        let doc = ToolRegistry::create_doc_check(&config, sender);
+        assert_eq!(doc.command[2..5], ["-p", "api", "--no-deps"]);
+    }
+}
@@ -1,13 +1,105 @@
 //! StatusCheck implementation for executing cargo commands
 
-use crate::display::{CheckStatus, StatusEvent};
-use std::process::{Command, Stdio};
-use std::time::Instant;
-use tokio::sync::mpsc;
+use crate::cache::results::{CachedResult, ResultCache};
+use crate::display::{CheckStatus, OutputStream, StatusEvent};
+use crate::tools::diagnostics::{
+    count_diagnostics, strip_ansi, Diagnostic, DiagnosticCollector, JSON_MESSAGE_FORMAT,
+};
+use crate::tools::test_results::{
+    parse_libtest_output, read_nextest_junit, test_targets, TestCase, TestOutcome, NEXTEST_TESTS_FAILED,
+};
+use crate::process::accounting::check_process_limit;
+use crate::process::{configure_jobserver, configure_process_group, kill_process_group};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::{mpsc, watch};
 
 // WARNING_PATTERN constant
 const WARNING_PATTERN: &str = "warning";
 
+/// Outcome of a single check execution
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: String,
+    /// Full command line that was run, including arguments added by cargo-status
+    pub command: Vec<String>,
+    pub status: CheckStatus,
+    /// Exit code of the command, if it ran to completion
+    pub exit_code: Option<i32>,
+    pub duration: Duration,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Test counts, for checks that run tests
+    pub tests: Option<TestCounts>,
+    /// Individual test results, for checks that run tests
+    pub test_cases: Vec<TestCase>,
+    /// Human-readable output of the command, one entry per line
+    pub output: Vec<String>,
+    /// Whether the result was replayed from the result cache
+    pub cached: bool,
+    /// Version reported by the tool that ran, e.g. `clippy 0.1.83 (90b35a6 2024-11-26)`
+    pub tool_version: Option<String>,
+}
+
+impl CheckResult {
+    /// Passed and failed tests, counted per test case when there are any
+    pub fn test_counts(&self) -> Option<TestCounts> {
+        if self.test_cases.is_empty() {
+            self.tests
+        } else {
+            Some(TestCounts::from_cases(&self.test_cases))
+        }
+    }
+}
+
+#[cfg(test)]
+impl CheckResult {
+    /// Result of running `cargo <name>` with the given status, for tests
+    pub fn for_test(name: &str, status: CheckStatus) -> Self {
+        Self {
+            name: name.to_string(),
+            command: vec!["cargo".to_string(), name.to_lowercase()],
+            duration: status.duration().unwrap_or_default(),
+            status,
+            exit_code: None,
+            diagnostics: Vec::new(),
+            tests: None,
+            test_cases: Vec::new(),
+            output: Vec::new(),
+            cached: false,
+            tool_version: None,
+        }
+    }
+
+    /// Sets the diagnostics of a test result
+    pub fn with_diagnostics(mut self, diagnostics: Vec<Diagnostic>) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+}
+
+/// Number of tests that passed and failed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestCounts {
+    pub passed: usize,
+    pub failed: usize,
+}
+
+impl TestCounts {
+    /// Counts the passed and failed tests among per-test results
+    pub fn from_cases(cases: &[TestCase]) -> Self {
+        let count = |outcome| cases.iter().filter(|case| case.outcome == outcome).count();
+        Self {
+            passed: count(TestOutcome::Passed),
+            failed: count(TestOutcome::Failed),
+        }
+    }
+}
+
 /// Represents a single cargo command to be executed
 ///
 /// Encapsulates command execution, output parsing, and result reporting.
@@ -17,6 +109,26 @@ pub struct StatusCheck {
     pub command: Vec<String>,
     pub warning_patterns: Vec<String>,
     pub verbose: bool,
+    pub json_diagnostics: bool,
+    pub timeout: Option<Duration>,
+    pub cancellation: Option<watch::Receiver<bool>>,
+    /// Names of checks that must pass before this one runs
+    pub depends_on: Vec<String>,
+    pub jobserver: Option<Arc<jobserver::Client>>,
+    /// JUnit file the command writes its test results to (nextest)
+    pub junit_path: Option<PathBuf>,
+    /// Files the command reads, with their contents, written just before it runs
+    pub config_files: Vec<(PathBuf, String)>,
+    /// Refuse to start while more than this many child processes are running
+    pub max_processes: Option<usize>,
+    /// Extra environment variables set for the command
+    pub env: Vec<(String, String)>,
+    /// Where results are replayed from while the check's inputs are unchanged
+    pub result_cache: Option<Arc<ResultCache>>,
+    /// Version reported by the tool, shown in reports
+    pub tool_version: Option<String>,
+    /// Report the check as skipped for this reason instead of running it
+    pub skip_reason: Option<String>,
     pub event_sender: Option<mpsc::UnboundedSender<StatusEvent>>,
 }
 
@@ -28,6 +140,18 @@ impl StatusCheck {
             command,
             warning_patterns: vec![WARNING_PATTERN.to_string()],
             verbose: false,
+            json_diagnostics: false,
+            timeout: None,
+            cancellation: None,
+            depends_on: Vec::new(),
+            jobserver: None,
+            junit_path: None,
+            config_files: Vec::new(),
+            max_processes: None,
+            env: Vec::new(),
+            result_cache: None,
+            tool_version: None,
+            skip_reason: None,
             event_sender: None,
         }
     }
@@ -57,25 +181,105 @@ impl StatusCheck {
         }
     }
 
-    /// Executes the command and returns parsed results
+    /// Enables cargo's JSON diagnostics output for this check
     ///
-    /// Handles both quiet and verbose modes, preserving colors when appropriate.
-    pub async fn run(&self) -> CheckStatus {
-        let start_time = Instant::now();
+    /// Only meaningful for compiler-backed commands (check, clippy, build, doc).
+    pub fn with_json_diagnostics(mut self, json: bool) -> Self {
+        self.json_diagnostics = json;
+        self
+    }
 
-        // Send start event
-        self.send_event(StatusEvent::CheckStarted {
-            name: self.name.clone(),
-        });
+    /// Sets a deadline after which the check's process group is killed
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
 
-        let quiet = !self.verbose;
-        let mut cmd = Command::new(&self.command[0]);
+    /// Sets the cancellation signal; the check stops once it reads `true`
+    pub fn with_cancellation(mut self, cancellation: watch::Receiver<bool>) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    /// Sets the checks that must pass before this one runs
+    pub fn with_dependencies(mut self, depends_on: Vec<String>) -> Self {
+        self.depends_on = depends_on;
+        self
+    }
+
+    /// Sets an environment variable for the command
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Points cargo at a separate target directory, if one is given
+    pub fn with_target_dir(self, target_dir: Option<PathBuf>) -> Self {
+        match target_dir {
+            Some(dir) => self.with_env("CARGO_TARGET_DIR", dir.to_string_lossy()),
+            None => self,
+        }
+    }
+
+    /// Shares a jobserver with the command's cargo process
+    pub fn with_jobserver(mut self, jobserver: Arc<jobserver::Client>) -> Self {
+        self.jobserver = Some(jobserver);
+        self
+    }
+
+    /// Reads per-test results from a JUnit file the command writes
+    pub fn with_junit_path(mut self, junit_path: Option<PathBuf>) -> Self {
+        self.junit_path = junit_path;
+        self
+    }
+
+    /// Writes a file the command reads just before it runs, so that only
+    /// listing the check leaves no trace
+    pub fn with_config_file(mut self, path: PathBuf, contents: impl Into<String>) -> Self {
+        self.config_files.push((path, contents.into()));
+        self
+    }
+
+    /// Sets the limit on child processes of this cargo-status run
+    pub fn with_max_processes(mut self, max_processes: Option<usize>) -> Self {
+        self.max_processes = max_processes;
+        self
+    }
+
+    /// Replays results from the cache while the check's inputs are unchanged
+    pub fn with_result_cache(mut self, result_cache: Option<Arc<ResultCache>>) -> Self {
+        self.result_cache = result_cache;
+        self
+    }
+
+    /// Records the version of the tool the check runs
+    pub fn with_tool_version(mut self, tool_version: Option<String>) -> Self {
+        self.tool_version = tool_version;
+        self
+    }
+
+    /// Skips the check instead of running it, e.g. when nothing it covers changed
+    pub fn with_skip_reason(mut self, skip_reason: Option<String>) -> Self {
+        self.skip_reason = skip_reason;
+        self
+    }
+
+    /// Whether cancellation has already been requested
+    fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(|cancellation| *cancellation.borrow())
+    }
 
-        // Build command arguments
+    /// Returns the full argument list passed to the program
+    ///
+    /// Adds color and message-format flags to the configured command.
+    pub fn command_args(&self) -> Vec<String> {
+        let quiet = !self.verbose;
         let mut args = Vec::new();
 
         // Force color output for cargo commands
-        if self.command[0] == "cargo" && self.command.len() > 1 {
+        if self.command.len() > 1 && self.command[0] == "cargo" {
             args.push(self.command[1].clone()); // cargo subcommand (e.g., "check", "clippy")
 
             // Special handling for nextest
@@ -96,6 +300,9 @@ impl StatusCheck {
                 }
             } else if self.command[1] != "fmt" {
                 args.push("--color=always".to_string());
+                if self.json_diagnostics {
+                    args.push(JSON_MESSAGE_FORMAT.to_string());
+                }
                 for arg in &self.command[2..] {
                     args.push(arg.clone());
                 }
@@ -105,10 +312,75 @@ impl StatusCheck {
                 }
             }
         } else {
-            args = self.command[1..].to_vec();
+            args = self.command.iter().skip(1).cloned().collect();
         }
 
-        cmd.args(&args);
+        args
+    }
+
+    /// Executes the command and returns parsed results
+    ///
+    /// Handles both quiet and verbose modes, preserving colors when appropriate.
+    pub async fn run(&self) -> CheckStatus {
+        self.execute().await.status
+    }
+
+    /// Executes the command and returns the status together with its diagnostics
+    ///
+    /// With a result cache, an unchanged check is replayed instead of run.
+    pub async fn execute(&self) -> CheckResult {
+        if let Some(ref reason) = self.skip_reason {
+            return self.skip(reason.clone());
+        }
+
+        let cache = self.result_cache.as_deref().and_then(|cache| {
+            let key = cache.key(&self.name, &self.command_line(), &self.env)?;
+            Some((cache, key))
+        });
+
+        if let Some((cache, ref key)) = cache
+            && !self.is_cancelled()
+            && let Some(cached) = cache.load(&self.name, key)
+        {
+            return self.replay(cached);
+        }
+
+        let result = self.execute_command().await;
+        if let Some((cache, key)) = cache
+            && let Err(e) = cache.store(&self.name, &key, &result)
+        {
+            eprintln!("Warning: could not cache the result of {}: {}", self.name, e);
+        }
+        result
+    }
+
+    /// Runs the command, ignoring the result cache
+    async fn execute_command(&self) -> CheckResult {
+        let start_time = Instant::now();
+
+        // Don't start anything once the run has been interrupted
+        if self.is_cancelled() {
+            let status = CheckStatus::Cancelled {
+                duration: start_time.elapsed(),
+            };
+            return self.complete(status, Vec::new());
+        }
+
+        // Send start event
+        self.send_event(StatusEvent::CheckStarted {
+            name: self.name.clone(),
+        });
+
+        let Some(program) = self.command.first() else {
+            let status = CheckStatus::Failed {
+                reason: "no command to run".to_string(),
+                duration: start_time.elapsed(),
+            };
+            return self.complete(status, Vec::new());
+        };
+        let mut cmd = Command::new(program);
+        cmd.args(self.command_args());
+        cmd.envs(self.env.iter().map(|(key, value)| (key, value)));
 
         // Send progress event
         self.send_event(StatusEvent::CheckProgress {
@@ -116,84 +388,176 @@ impl StatusCheck {
             message: "executing...".to_string(),
         });
 
-        // Safety: Check for runaway processes before executing
-        if let Ok(output) = std::process::Command::new("sh")
-            .args(["-c", "ps aux | grep -c '[c]argo' || echo 0"])
-            .output()
-            && let Ok(count_str) = String::from_utf8(output.stdout)
-            && let Ok(count) = count_str.trim().parse::<usize>()
-            && count > 50 {
-            let duration = start_time.elapsed();
+        // Safety: Refuse to add to a runaway process tree
+        if let Some(limit) = self.max_processes
+            && let Some(reason) = check_process_limit(limit)
+        {
             let status = CheckStatus::Failed {
-                reason: format!(
-                    "Too many cargo processes detected ({}), aborting to prevent system overload",
-                    count
-                ),
-                duration,
+                reason,
+                duration: start_time.elapsed(),
             };
-            self.send_event(StatusEvent::CheckCompleted {
-                name: self.name.clone(),
-                status: status.clone(),
-            });
-            return status;
+            return self.complete(status, Vec::new());
         }
 
         // Execute command - always capture output for metrics
-        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
-
-        let (success, combined_output) = match cmd.output() {
-            Ok(output) => {
-                // If verbose mode, print the output
-                if !quiet {
-                    // Print stdout with colors preserved
-                    if !output.stdout.is_empty() {
-                        print!("{}", String::from_utf8_lossy(&output.stdout));
-                    }
-                    // Print stderr with colors preserved
-                    if !output.stderr.is_empty() {
-                        eprint!("{}", String::from_utf8_lossy(&output.stderr));
-                    }
-                }
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        configure_process_group(&mut cmd);
+        if let Some(ref jobserver) = self.jobserver {
+            configure_jobserver(&mut cmd, jobserver);
+        }
 
-                let mut combined = String::from_utf8_lossy(&output.stdout).into_owned();
-                combined.push_str(&String::from_utf8_lossy(&output.stderr));
-                (output.status.success(), combined)
+        for (path, contents) in &self.config_files {
+            let written = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(path, contents));
+            if let Err(e) = written {
+                let status = CheckStatus::Failed {
+                    reason: format!("Failed to write {}: {}", path.display(), e),
+                    duration: start_time.elapsed(),
+                };
+                return self.complete(status, Vec::new());
             }
+        }
+
+        // Don't mistake results from an earlier run for this one
+        if let Some(ref junit_path) = self.junit_path {
+            let _ = std::fs::remove_file(junit_path);
+        }
+
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
             Err(e) => {
                 let duration = start_time.elapsed();
                 let status = CheckStatus::Failed {
                     reason: format!("Failed to run command: {}", e),
                     duration,
                 };
+                return self.complete(status, Vec::new());
+            }
+        };
 
-                self.send_event(StatusEvent::CheckCompleted {
-                    name: self.name.clone(),
-                    status: status.clone(),
-                });
+        // Read both streams concurrently so neither pipe can fill up and block the child
+        let (line_sender, mut line_receiver) = mpsc::unbounded_channel();
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(forward_lines(stdout, OutputStream::Stdout, line_sender.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(forward_lines(stderr, OutputStream::Stderr, line_sender));
+        }
 
-                return status;
+        let mut collector =
+            DiagnosticCollector::new(self.json_diagnostics, self.warning_patterns.clone());
+        let mut stdout_text = String::new();
+        let mut output = Vec::new();
+
+        let deadline = async {
+            match self.timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
             }
         };
+        tokio::pin!(deadline);
+
+        let cancelled = wait_for_cancellation(self.cancellation.clone());
+        tokio::pin!(cancelled);
+
+        let interruption = loop {
+            tokio::select! {
+                line = line_receiver.recv() => {
+                    let Some((stream, line)) = line else {
+                        break None;
+                    };
+                    if stream == OutputStream::Stdout {
+                        stdout_text.push_str(&line);
+                        stdout_text.push('\n');
+                    }
 
-        // Parse results
-        let duration = start_time.elapsed();
-        let warnings = self
-            .warning_patterns
-            .iter()
-            .map(|pattern| combined_output.matches(pattern).count())
-            .sum();
+                    if let Some(text) = collector.push_line(&line) {
+                        for text_line in text.lines() {
+                            self.emit_output(stream, text_line);
+                            output.push(text_line.to_string());
+                        }
+                    }
+                }
+                _ = &mut deadline => {
+                    break Some(CheckStatus::TimedOut {
+                        timeout: self.timeout.unwrap_or_default(),
+                        duration: start_time.elapsed(),
+                    });
+                }
+                _ = &mut cancelled => {
+                    break Some(CheckStatus::Cancelled {
+                        duration: start_time.elapsed(),
+                    });
+                }
+            }
+        };
 
-        let errors =
-            combined_output.matches("error:").count() + combined_output.matches("error[").count();
+        if let Some(status) = interruption {
+            kill_process_group(&mut child);
+            let _ = child.wait().await;
+            let mut result = self.complete(status, collector.finish());
+            result.output = output;
+            return result;
+        }
 
-        let (_test_passed, test_failed) = if self.name == "Test" {
-            parse_test_results(&combined_output)
+        let exit_status = match child.wait().await {
+            Ok(exit_status) => exit_status,
+            Err(e) => {
+                let duration = start_time.elapsed();
+                let status = CheckStatus::Failed {
+                    reason: format!("Failed to wait for command: {}", e),
+                    duration,
+                };
+                return self.complete(status, collector.finish());
+            }
+        };
+
+        // Parse results
+        let duration = start_time.elapsed();
+        let diagnostics = collector.finish();
+        let (errors, warnings) = count_diagnostics(&diagnostics);
+
+        let mut missing_junit = None;
+        let test_cases = if self.name != "Test" {
+            Vec::new()
+        } else if let Some(ref junit_path) = self.junit_path {
+            read_nextest_junit(junit_path).unwrap_or_else(|| {
+                missing_junit = Some(junit_path);
+                Vec::new()
+            })
         } else {
-            (0, 0)
+            let targets = test_targets(output.iter().map(String::as_str));
+            parse_libtest_output(&stdout_text, &targets)
         };
 
+        // Per-test results cover every test binary and nextest, which prints
+        // no `test result:` summaries
+        let tests = (self.name == "Test").then(|| {
+            if test_cases.is_empty() {
+                let (passed, failed) = parse_test_results(&stdout_text);
+                TestCounts { passed, failed }
+            } else {
+                TestCounts::from_cases(&test_cases)
+            }
+        });
+        let test_failed = tests.map_or(0, |tests| tests.failed);
+
+        // Once the tests ran, a missing JUnit file means the per-test results
+        // were lost rather than that there were none
+        let tests_ran = exit_status.success() || exit_status.code() == Some(NEXTEST_TESTS_FAILED);
+
         // Determine final status
-        let status = if !success || errors > 0 || test_failed > 0 {
+        let status = if let Some(junit_path) = missing_junit.filter(|_| tests_ran) {
+            CheckStatus::Failed {
+                reason: format!("nextest wrote no JUnit results to {}", junit_path.display()),
+                duration,
+            }
+        } else if !exit_status.success() || errors > 0 || test_failed > 0 {
             CheckStatus::Error {
                 errors: errors + test_failed,
                 warnings,
@@ -208,39 +572,176 @@ impl StatusCheck {
             }
         };
 
-        // Send completion event
+        let mut result = self.complete(status, diagnostics);
+        result.exit_code = exit_status.code();
+        result.tests = tests;
+        result.test_cases = test_cases;
+        result.output = output;
+        result
+    }
+
+    /// Full command line, including arguments added by cargo-status
+    pub fn command_line(&self) -> Vec<String> {
+        self.command
+            .first()
+            .cloned()
+            .into_iter()
+            .chain(self.command_args())
+            .collect()
+    }
+
+    /// Reports the check as skipped without running it
+    pub fn skip(&self, reason: String) -> CheckResult {
+        self.complete(CheckStatus::Skipped { reason }, Vec::new())
+    }
+
+    /// Reports the check as failed for a reason other than its command
+    pub fn fail(&self, reason: String, duration: Duration) -> CheckResult {
+        self.complete(CheckStatus::Failed { reason, duration }, Vec::new())
+    }
+
+    /// Forwards a line of output to the display, echoing it in verbose mode
+    fn emit_output(&self, stream: OutputStream, line: &str) {
+        if self.verbose {
+            // Print with colors preserved, on the stream it came from
+            match stream {
+                OutputStream::Stdout => println!("{}", line),
+                OutputStream::Stderr => eprintln!("{}", line),
+            }
+        }
+
+        self.send_event(StatusEvent::CheckOutput {
+            name: self.name.clone(),
+            stream,
+            line: line.to_string(),
+        });
+
+        // Cargo reports when another process holds the lock it needs
+        if let Some(reason) = file_lock_wait(line) {
+            self.send_event(StatusEvent::CheckWaiting {
+                name: self.name.clone(),
+                reason,
+            });
+        }
+    }
+
+    /// Reports a cached result as if the command had just run
+    fn replay(&self, cached: CachedResult) -> CheckResult {
+        self.send_event(StatusEvent::CheckStarted {
+            name: self.name.clone(),
+        });
+        for line in &cached.output {
+            if self.verbose {
+                println!("{}", line);
+            }
+            self.send_event(StatusEvent::CheckOutput {
+                name: self.name.clone(),
+                stream: OutputStream::Stdout,
+                line: line.clone(),
+            });
+        }
+        self.send_event(StatusEvent::CheckCached {
+            name: self.name.clone(),
+        });
+
+        let mut result = self.complete(cached.status.to_status(), cached.diagnostics);
+        result.exit_code = cached.exit_code;
+        result.tests = cached.tests;
+        result.test_cases = cached.test_cases;
+        result.output = cached.output;
+        result.cached = true;
+        result
+    }
+
+    /// Sends the completion event and builds the final result
+    fn complete(&self, status: CheckStatus, diagnostics: Vec<Diagnostic>) -> CheckResult {
         self.send_event(StatusEvent::CheckCompleted {
             name: self.name.clone(),
             status: status.clone(),
         });
 
-        status
+        CheckResult {
+            name: self.name.clone(),
+            command: self.command_line(),
+            duration: status.duration().unwrap_or_default(),
+            status,
+            exit_code: None,
+            diagnostics,
+            tests: None,
+            test_cases: Vec::new(),
+            output: Vec::new(),
+            cached: false,
+            tool_version: self.tool_version.clone(),
+        }
+    }
+}
+
+/// Extracts the reason from cargo's `Blocking waiting for file lock on ...` message
+fn file_lock_wait(line: &str) -> Option<String> {
+    let plain = strip_ansi(line);
+    let (_, reason) = plain.split_once("Blocking ")?;
+    reason
+        .starts_with("waiting for file lock")
+        .then(|| reason.trim().to_string())
+}
+
+/// Resolves once cancellation is requested; never resolves without a signal
+async fn wait_for_cancellation(cancellation: Option<watch::Receiver<bool>>) {
+    if let Some(mut cancellation) = cancellation
+        && cancellation.wait_for(|cancelled| *cancelled).await.is_ok()
+    {
+        return;
+    }
+    std::future::pending().await
+}
+
+/// Reads `reader` line by line and forwards each line tagged with its stream
+///
+/// Lines are decoded lossily so that non-UTF-8 output cannot stall the check.
+async fn forward_lines<R: AsyncRead + Unpin>(
+    reader: R,
+    stream: OutputStream,
+    sender: mpsc::UnboundedSender<(OutputStream, String)>,
+) {
+    let mut reader = BufReader::new(reader);
+    let mut buffer = Vec::new();
+
+    loop {
+        buffer.clear();
+        match reader.read_until(b'\n', &mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&buffer);
+                let line = line.trim_end_matches(['\n', '\r']).to_string();
+                if sender.send((stream, line)).is_err() {
+                    break;
+                }
+            }
+        }
     }
 }
 
 /// Parse test results from output to extract passed/failed counts
+///
+/// Adds up the `test result: ok. X passed; Y failed` line of every test binary.
 fn parse_test_results(output: &str) -> (usize, usize) {
-    // Look for patterns like "test result: ok. X passed; Y failed"
-    if let Some(result_line) = output.lines().find(|line| line.contains("test result:")) {
+    let mut passed = 0;
+    let mut failed = 0;
+
+    for result_line in output.lines().filter(|line| line.contains("test result:")) {
         let words: Vec<&str> = result_line.split_whitespace().collect();
-        
-        let mut passed = 0;
-        let mut failed = 0;
-        
-        for i in 0..words.len() {
-            if words[i] == "passed;" && i > 0
+        for i in 1..words.len() {
+            if words[i] == "passed;"
                 && let Ok(count) = words[i - 1].parse::<usize>() {
-                passed = count;
-            } else if words[i] == "failed;" && i > 0
+                passed += count;
+            } else if words[i] == "failed;"
                 && let Ok(count) = words[i - 1].parse::<usize>() {
-                failed = count;
+                failed += count;
             }
         }
-
-        return (passed, failed);
     }
 
-    (0, 0)
+    (passed, failed)
 }
 
 #[cfg(test)]
@@ -258,6 +759,39 @@ mod tests {
         let (passed2, failed2) = parse_test_results(output2);
         assert_eq!(passed2, 8);
         assert_eq!(failed2, 3);
+
+        let several = "test result: ok. 2 passed; 0 failed; 0 ignored\n\
+                       test result: FAILED. 3 passed; 1 failed; 0 ignored\n";
+        assert_eq!(parse_test_results(several), (5, 1));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_counts_every_test_binary() {
+        let output = "\
+     Running unittests src/lib.rs (target/debug/deps/demo-1a2b)
+
+running 2 tests
+test tests::adds ... ok
+test tests::subtracts ... ok
+
+test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out
+
+     Running tests/api.rs (target/debug/deps/api-3c4d)
+
+running 2 tests
+test lists ... ok
+test creates ... FAILED
+
+test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out
+";
+        let dir = tempfile::TempDir::new().unwrap();
+        let output_path = dir.path().join("output.txt");
+        std::fs::write(&output_path, output).unwrap();
+        let cmd = vec!["sh".to_string(), "-c".to_string(), format!("cat {}; exit 101", output_path.display())];
+
+        let result = StatusCheck::new("Test", cmd).execute().await;
+        assert_eq!(result.tests, Some(TestCounts { passed: 3, failed: 1 }));
     }
 
     #[test]
@@ -269,4 +803,191 @@ mod tests {
         assert!(!check.verbose);
         assert_eq!(check.warning_patterns, vec!["warning"]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_command_args_json_diagnostics() {
+        let cmd = vec!["cargo".to_string(), "check".to_string(), "--workspace".to_string()];
+        let check = StatusCheck::new("Check", cmd).with_json_diagnostics(true);
+
+        assert_eq!(
+            check.command_args(),
+            vec!["check", "--color=always", JSON_MESSAGE_FORMAT, "--workspace"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_streams_output_events() {
+        let cmd = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "echo out; echo 'warning: careful' >&2".to_string(),
+        ];
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let check = StatusCheck::new("Shell", cmd).with_event_sender(sender);
+
+        let result = check.execute().await;
+        assert!(matches!(result.status, CheckStatus::Warning { warnings: 1, .. }));
+
+        let mut lines = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            if let StatusEvent::CheckOutput { stream, line, .. } = event {
+                lines.push((stream, line));
+            }
+        }
+        lines.sort_by_key(|(stream, _)| *stream == OutputStream::Stderr);
+        assert_eq!(
+            lines,
+            vec![
+                (OutputStream::Stdout, "out".to_string()),
+                (OutputStream::Stderr, "warning: careful".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_command_fails() {
+        let status = StatusCheck::new("Build", Vec::new()).run().await;
+        assert!(
+            matches!(status, CheckStatus::Failed { ref reason, .. } if reason == "no command to run"),
+            "{:?}",
+            status
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_config_files_written_before_running() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("tool/config.toml");
+        let cmd = vec!["cat".to_string(), path.display().to_string()];
+        let check = StatusCheck::new("Cat", cmd).with_config_file(path.clone(), "answer = 42");
+        assert!(!path.exists());
+
+        let result = check.execute().await;
+        assert!(matches!(result.status, CheckStatus::Success { .. }), "{:?}", result.status);
+        assert_eq!(result.output, vec!["answer = 42"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_missing_junit_results_fail() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let junit_path = dir.path().join("junit.xml");
+        let check = |script: &str| {
+            StatusCheck::new("Test", vec!["sh".to_string(), "-c".to_string(), script.to_string()])
+                .with_junit_path(Some(junit_path.clone()))
+        };
+
+        let status = check("true").run().await;
+        assert!(
+            matches!(status, CheckStatus::Failed { ref reason, .. } if reason.contains("no JUnit results")),
+            "{:?}",
+            status
+        );
+
+        // A build that fails before any test runs writes no JUnit file either
+        let status = check("exit 101").run().await;
+        assert!(matches!(status, CheckStatus::Error { .. }), "{:?}", status);
+
+        let xml = r#"<testsuites><testsuite name="lib"><testcase name="adds"/></testsuite></testsuites>"#;
+        let result = check(&format!("echo '{}' > {}", xml, junit_path.display())).execute().await;
+        assert!(matches!(result.status, CheckStatus::Success { .. }), "{:?}", result.status);
+        assert_eq!(result.test_cases.len(), 1);
+
+        // nextest prints no `test result:` lines; the counts come from the JUnit file
+        let xml = r#"<testsuites><testsuite name="lib"><testcase name="adds"/></testsuite><testsuite name="api"><testcase name="lists"/><testcase name="creates"><failure message="boom"/></testcase></testsuite></testsuites>"#;
+        let script = format!("echo '{}' > {}; exit {}", xml, junit_path.display(), NEXTEST_TESTS_FAILED);
+        let result = check(&script).execute().await;
+        assert!(matches!(result.status, CheckStatus::Error { errors: 1, .. }), "{:?}", result.status);
+        assert_eq!(result.tests, Some(TestCounts { passed: 2, failed: 1 }));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_times_out() {
+        let cmd = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "sleep 30 & sleep 30".to_string(),
+        ];
+        let check = StatusCheck::new("Slow", cmd).with_timeout(Some(Duration::from_millis(200)));
+
+        let started = Instant::now();
+        let status = check.run().await;
+
+        assert!(matches!(status, CheckStatus::TimedOut { .. }));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_cancelled() {
+        let cmd = vec!["sleep".to_string(), "30".to_string()];
+        let (cancel, cancellation) = watch::channel(false);
+        let check = StatusCheck::new("Slow", cmd).with_cancellation(cancellation);
+
+        let handle = tokio::spawn(async move { check.run().await });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        cancel.send(true).unwrap();
+
+        let status = tokio::time::timeout(Duration::from_secs(10), handle)
+            .await
+            .expect("cancelled check should stop promptly")
+            .unwrap();
+        assert!(matches!(status, CheckStatus::Cancelled { .. }));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_passes_jobserver() {
+        let cmd = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "echo \"$CARGO_MAKEFLAGS\"".to_string(),
+        ];
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let jobserver = crate::process::create_jobserver(2).unwrap();
+        let check = StatusCheck::new("Shell", cmd)
+            .with_jobserver(jobserver)
+            .with_event_sender(sender);
+
+        check.run().await;
+
+        let mut makeflags = String::new();
+        while let Ok(event) = receiver.try_recv() {
+            if let StatusEvent::CheckOutput { line, .. } = event {
+                makeflags = line;
+            }
+        }
+        assert!(makeflags.contains("--jobserver-auth="));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_refuses_over_process_limit() {
+        let mut sleeper = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let check = StatusCheck::new("Echo", vec!["echo".to_string()])
+            .with_max_processes(Some(0));
+
+        let status = check.run().await;
+        let _ = sleeper.kill();
+        let _ = sleeper.wait();
+
+        let CheckStatus::Failed { reason, .. } = status else {
+            panic!("expected the check to be refused, got {:?}", status);
+        };
+        assert!(reason.starts_with("Too many child processes"));
+        assert!(reason.contains("sleep"));
+    }
+
+    #[test]
+    fn test_file_lock_wait() {
+        let line = "\u{1b}[1m\u{1b}[36m    Blocking\u{1b}[0m waiting for file lock on build directory";
+        assert_eq!(
+            file_lock_wait(line),
+            Some("waiting for file lock on build directory".to_string())
+        );
+        assert_eq!(file_lock_wait("   Compiling demo v0.1.0"), None);
+    }
+}
@@ -0,0 +1,366 @@
+//! Per-test results for checks that run tests
+//!
+//! `cargo test` results are read from libtest's `test <name> ... ok` lines and
+//! the captured output printed for failing tests. `cargo nextest` runs are
+//! configured to write a JUnit file, which is read back instead.
+
+use crate::tools::diagnostics::strip_ansi;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use toml::{Table, Value};
+
+/// Tool config that turns on JUnit output for every nextest profile
+///
+/// Custom nextest profiles inherit from `default`, so setting the path there is enough.
+pub const NEXTEST_JUNIT_CONFIG: &str = "[profile.default.junit]\npath = \"junit.xml\"\n";
+
+/// nextest's exit code when the tests ran and some of them failed
+pub const NEXTEST_TESTS_FAILED: i32 = 100;
+
+/// Where nextest writes the JUnit file of a run with `profile`
+///
+/// The repository's `.config/nextest.toml` beats [`NEXTEST_JUNIT_CONFIG`],
+/// so its `store.dir` and `junit.path` are used when it sets them, following
+/// the profile's `inherits` chain down to `default`.
+pub fn nextest_junit_path(root: &Path, target_dir: &Path, profile: &str) -> PathBuf {
+    let repo: Table = std::fs::read_to_string(root.join(".config/nextest.toml"))
+        .ok()
+        .and_then(|contents| toml::from_str(&contents).ok())
+        .unwrap_or_default();
+
+    let store = match repo.get("store").and_then(|store| store.get("dir")).and_then(Value::as_str) {
+        Some(dir) => root.join(dir),
+        None => target_dir.join("nextest"),
+    };
+    let junit = repo_junit_path(&repo, profile).unwrap_or("junit.xml");
+    store.join(profile).join(junit)
+}
+
+/// `junit.path` of a profile in the repository's nextest config, if set
+fn repo_junit_path<'a>(repo: &'a Table, profile: &str) -> Option<&'a str> {
+    let profiles = repo.get("profile")?.as_table()?;
+    let mut name = profile;
+    // Bounded, in case of an `inherits` cycle nextest would reject anyway
+    for _ in 0..=profiles.len() {
+        let table = profiles.get(name);
+        let path = table
+            .and_then(|table| table.get("junit"))
+            .and_then(|junit| junit.get("path"))
+            .and_then(Value::as_str);
+        if path.is_some() || name == "default" {
+            return path;
+        }
+        name = table
+            .and_then(|table| table.get("inherits"))
+            .and_then(Value::as_str)
+            .unwrap_or("default");
+    }
+    None
+}
+
+/// Outcome of a single test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+/// Result of a single test
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestCase {
+    /// Full test path, e.g. `tests::parses_empty_input`
+    pub name: String,
+    /// Test binary the test belongs to, when known
+    pub suite: Option<String>,
+    /// Package the test belongs to, when known
+    #[serde(default)]
+    pub crate_name: Option<String>,
+    pub outcome: TestOutcome,
+    /// Time the test took, when the runner reports it
+    pub duration: Option<Duration>,
+    /// Failure message or captured output of a failed test
+    pub message: Option<String>,
+}
+
+/// Parse libtest's human-readable output into test cases
+///
+/// `targets` are the test targets cargo ran, in order (see [`test_targets`]).
+/// Each binary's output starts with `running N tests`, so the n-th such line
+/// starts the tests of the n-th target, which becomes their suite.
+pub fn parse_libtest_output(output: &str, targets: &[String]) -> Vec<TestCase> {
+    let mut cases: Vec<TestCase> = Vec::new();
+    let mut capture: Option<(String, Vec<&str>)> = None;
+    let mut binaries = targets.iter();
+    let mut suite: Option<&String> = None;
+
+    for line in output.lines() {
+        if is_binary_start(line) {
+            finish_capture(&mut cases, capture.take());
+            suite = binaries.next();
+            continue;
+        }
+
+        // Captured output of a failed test: `---- name stdout ----` up to the next section
+        if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"))
+        {
+            finish_capture(&mut cases, capture.take());
+            capture = Some((name.to_string(), Vec::new()));
+            continue;
+        }
+        if let Some((_, ref mut lines)) = capture {
+            if line == "failures:" || line.starts_with("test result:") {
+                finish_capture(&mut cases, capture.take());
+            } else {
+                lines.push(line);
+            }
+            continue;
+        }
+
+        let Some((name, result)) = line
+            .strip_prefix("test ")
+            .and_then(|rest| rest.rsplit_once(" ... "))
+        else {
+            continue;
+        };
+        let outcome = match result {
+            "ok" => TestOutcome::Passed,
+            "FAILED" => TestOutcome::Failed,
+            result if result.starts_with("ignored") => TestOutcome::Ignored,
+            _ => continue,
+        };
+        cases.push(TestCase {
+            name: name.to_string(),
+            suite: suite.cloned(),
+            crate_name: None,
+            outcome,
+            duration: None,
+            message: None,
+        });
+    }
+    finish_capture(&mut cases, capture);
+
+    cases
+}
+
+/// Whether a line is libtest's `running N tests` header
+fn is_binary_start(line: &str) -> bool {
+    line.strip_prefix("running ")
+        .and_then(|rest| rest.split_once(' '))
+        .is_some_and(|(count, rest)| count.parse::<usize>().is_ok() && rest.starts_with("test"))
+}
+
+/// Test targets in the order cargo ran them, from its `Running` and
+/// `Doc-tests` lines
+///
+/// `Running unittests src/lib.rs (target/debug/deps/my_crate-1a2b3c)` runs
+/// the `my_crate` target; the hash cargo appends to the binary is dropped.
+pub fn test_targets<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    lines
+        .into_iter()
+        .filter_map(|line| test_target(strip_ansi(line).trim()))
+        .collect()
+}
+
+fn test_target(line: &str) -> Option<String> {
+    if let Some(name) = line.strip_prefix("Doc-tests ") {
+        return Some(name.trim().to_string());
+    }
+    let binary = line
+        .strip_prefix("Running ")?
+        .rsplit_once(" (")?
+        .1
+        .strip_suffix(')')?;
+    let file_name = Path::new(binary).file_stem()?.to_str()?;
+    let target = match file_name.rsplit_once('-') {
+        Some((target, hash)) if hash.chars().all(|c| c.is_ascii_hexdigit()) => target,
+        _ => file_name,
+    };
+    Some(target.to_string())
+}
+
+/// Attach captured output to the failed test it belongs to
+fn finish_capture(cases: &mut [TestCase], capture: Option<(String, Vec<&str>)>) {
+    let Some((name, lines)) = capture else {
+        return;
+    };
+    let message = lines.join("\n").trim().to_string();
+    if let Some(case) = cases
+        .iter_mut()
+        .find(|case| case.name == name && case.outcome == TestOutcome::Failed && case.message.is_none())
+    {
+        case.message = Some(message);
+    }
+}
+
+/// Read the test cases from a JUnit file written by nextest
+pub fn read_nextest_junit(path: &Path) -> Option<Vec<TestCase>> {
+    let xml = std::fs::read_to_string(path).ok()?;
+    parse_junit(&xml)
+}
+
+/// Parse test cases from a JUnit XML document
+fn parse_junit(xml: &str) -> Option<Vec<TestCase>> {
+    let document = roxmltree::Document::parse(xml).ok()?;
+    let mut cases = Vec::new();
+
+    for suite in document.descendants().filter(|node| node.has_tag_name("testsuite")) {
+        let suite_name = suite.attribute("name").map(String::from);
+        for case in suite.children().filter(|node| node.has_tag_name("testcase")) {
+            let problem = case
+                .children()
+                .find(|node| node.has_tag_name("failure") || node.has_tag_name("error"));
+            let outcome = if problem.is_some() {
+                TestOutcome::Failed
+            } else if case.children().any(|node| node.has_tag_name("skipped")) {
+                TestOutcome::Ignored
+            } else {
+                TestOutcome::Passed
+            };
+
+            // Prefer the captured output over the one-line message
+            let message = problem.and_then(|node| {
+                let output = case
+                    .children()
+                    .find(|node| node.has_tag_name("system-out") || node.has_tag_name("system-err"))
+                    .and_then(|node| node.text())
+                    .or(node.text())
+                    .or(node.attribute("message"));
+                output.map(|text| text.trim().to_string())
+            });
+
+            cases.push(TestCase {
+                name: case.attribute("name").unwrap_or_default().to_string(),
+                suite: suite_name.clone(),
+                // nextest names suites `package` or `package::binary`
+                crate_name: suite_name
+                    .as_deref()
+                    .and_then(|suite| suite.split("::").next())
+                    .map(String::from),
+                outcome,
+                duration: case
+                    .attribute("time")
+                    .and_then(|time| time.parse::<f64>().ok())
+                    .and_then(|secs| Duration::try_from_secs_f64(secs).ok()),
+                message,
+            });
+        }
+    }
+
+    Some(cases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_libtest_output() {
+        let output = "\
+running 3 tests
+test tests::adds ... ok
+test tests::slow ... ignored, takes a minute
+test tests::divides ... FAILED
+
+failures:
+
+---- tests::divides stdout ----
+thread 'tests::divides' panicked at src/lib.rs:9:9:
+attempt to divide by zero
+
+failures:
+    tests::divides
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out
+";
+        let cases = parse_libtest_output(output, &[]);
+        assert_eq!(cases.len(), 3);
+        assert_eq!(cases[0].outcome, TestOutcome::Passed);
+        assert_eq!(cases[1].outcome, TestOutcome::Ignored);
+        assert_eq!(cases[2].name, "tests::divides");
+        assert_eq!(cases[2].outcome, TestOutcome::Failed);
+        assert_eq!(
+            cases[2].message.as_deref(),
+            Some("thread 'tests::divides' panicked at src/lib.rs:9:9:\nattempt to divide by zero")
+        );
+    }
+
+    #[test]
+    fn test_libtest_suites() {
+        let stderr = [
+            "     \u{1b}[1m\u{1b}[92mRunning\u{1b}[0m unittests src/lib.rs (target/debug/deps/demo_core-3110576fc3c3c96d)",
+            "     Running tests/smoke.rs (target/debug/deps/smoke-d3244b10e13794ed)",
+            "   Doc-tests demo_core",
+        ];
+        let targets = test_targets(stderr);
+        assert_eq!(targets, vec!["demo_core", "smoke", "demo_core"]);
+
+        let stdout = "\
+running 1 test
+test tests::adds ... ok
+
+running 1 test
+test starts ... ok
+
+running 1 test
+test src/lib.rs - add (line 3) ... ok
+";
+        let suites: Vec<_> = parse_libtest_output(stdout, &targets)
+            .into_iter()
+            .map(|case| case.suite.unwrap())
+            .collect();
+        assert_eq!(suites, targets);
+    }
+
+    #[test]
+    fn test_nextest_junit_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        let target = root.join("target");
+        assert_eq!(
+            nextest_junit_path(root, &target, "default"),
+            target.join("nextest/default/junit.xml")
+        );
+
+        std::fs::create_dir_all(root.join(".config")).unwrap();
+        std::fs::write(
+            root.join(".config/nextest.toml"),
+            "[store]\ndir = \"out/nextest\"\n\
+             [profile.default.junit]\npath = \"reports/junit.xml\"\n\
+             [profile.ci.junit]\npath = \"ci.xml\"\n\
+             [profile.nightly]\ninherits = \"ci\"\n",
+        )
+        .unwrap();
+        let store = root.join("out/nextest");
+        assert_eq!(nextest_junit_path(root, &target, "default"), store.join("default/reports/junit.xml"));
+        assert_eq!(nextest_junit_path(root, &target, "ci"), store.join("ci/ci.xml"));
+        assert_eq!(nextest_junit_path(root, &target, "nightly"), store.join("nightly/ci.xml"));
+        assert_eq!(nextest_junit_path(root, &target, "other"), store.join("other/reports/junit.xml"));
+    }
+
+    #[test]
+    fn test_parse_nextest_junit() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="nextest-run" tests="2" failures="1" errors="0">
+    <testsuite name="demo" tests="2" disabled="0" errors="0" failures="1">
+        <testcase name="tests::adds" classname="demo" timestamp="2024-01-01T00:00:00Z" time="0.012">
+        </testcase>
+        <testcase name="tests::divides" classname="demo" time="0.5">
+            <failure type="test failure">thread panicked</failure>
+            <system-out>attempt to divide by zero</system-out>
+        </testcase>
+    </testsuite>
+</testsuites>"#;
+
+        let cases = parse_junit(xml).unwrap();
+        assert_eq!(cases.len(), 2);
+        assert_eq!(cases[0].suite.as_deref(), Some("demo"));
+        assert_eq!(cases[0].crate_name.as_deref(), Some("demo"));
+        assert_eq!(cases[0].duration, Some(Duration::from_millis(12)));
+        assert_eq!(cases[1].outcome, TestOutcome::Failed);
+        assert_eq!(cases[1].message.as_deref(), Some("attempt to divide by zero"));
+    }
+}
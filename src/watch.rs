@@ -0,0 +1,162 @@
+//! Watching the workspace for changes
+//!
+//! `--watch` re-runs the checks whenever a source file changes. The watcher
+//! polls the files [`source_files`] lists, so `.gitignore`d paths, build
+//! output in `target` and the reports a run writes never trigger a run, and
+//! waits for edits to settle before reporting them so saving several files
+//! starts a single run.
+
+use crate::cache::results::source_files;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How often the workspace is scanned for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// How long the workspace must stay unchanged before a run starts
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Size and modification time of a file, enough to notice edits
+type Stamp = (u64, Option<SystemTime>);
+
+/// Polls the files of a workspace for changes
+#[derive(Debug)]
+pub struct Watcher {
+    root: PathBuf,
+    /// Files cargo-status writes, which are not watched
+    outputs: Vec<PathBuf>,
+    snapshot: HashMap<PathBuf, Stamp>,
+    /// Changes seen but not yet returned, kept if a wait is abandoned
+    pending: BTreeSet<PathBuf>,
+    poll_interval: Duration,
+    debounce: Duration,
+}
+
+impl Watcher {
+    /// Starts watching the workspace at `root` from its current state,
+    /// leaving out the `outputs` a run writes
+    pub fn new(root: PathBuf, outputs: Vec<PathBuf>) -> Self {
+        let snapshot = snapshot(&root, &outputs);
+        Self {
+            root,
+            outputs,
+            snapshot,
+            pending: BTreeSet::new(),
+            poll_interval: POLL_INTERVAL,
+            debounce: DEBOUNCE,
+        }
+    }
+
+    /// Sets how often to poll and how long changes must settle
+    pub fn with_timing(mut self, poll_interval: Duration, debounce: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self.debounce = debounce;
+        self
+    }
+
+    /// Files added, removed or modified since the last scan, relative to the root
+    pub fn scan(&mut self) -> Vec<PathBuf> {
+        let current = snapshot(&self.root, &self.outputs);
+        let mut changed: Vec<PathBuf> = current
+            .iter()
+            .filter(|(path, stamp)| self.snapshot.get(*path) != Some(*stamp))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(
+            self.snapshot
+                .keys()
+                .filter(|path| !current.contains_key(*path))
+                .cloned(),
+        );
+        changed.sort();
+        self.snapshot = current;
+        changed
+    }
+
+    /// Waits until files change and then stay unchanged for the debounce period
+    ///
+    /// Returns every file that changed in the meantime. Cancelling the wait
+    /// keeps changes already seen for the next call.
+    pub async fn wait_for_change(&mut self) -> Vec<PathBuf> {
+        while self.pending.is_empty() {
+            tokio::time::sleep(self.poll_interval).await;
+            let changed = self.scan();
+            self.pending.extend(changed);
+        }
+
+        // Let a burst of saves (or a branch switch) finish first
+        loop {
+            tokio::time::sleep(self.debounce).await;
+            let changed = self.scan();
+            if changed.is_empty() {
+                break;
+            }
+            self.pending.extend(changed);
+        }
+        std::mem::take(&mut self.pending).into_iter().collect()
+    }
+}
+
+fn snapshot(root: &Path, outputs: &[PathBuf]) -> HashMap<PathBuf, Stamp> {
+    source_files(root, outputs)
+        .into_iter()
+        .filter_map(|path| {
+            let metadata = fs::metadata(root.join(&path)).ok()?;
+            Some((path, (metadata.len(), metadata.modified().ok())))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_watcher_reports_source_changes() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "pub fn demo() {}\n").unwrap();
+        let mut watcher = Watcher::new(dir.path().to_path_buf(), Vec::new())
+            .with_timing(Duration::from_millis(10), Duration::from_millis(10));
+        assert!(watcher.scan().is_empty());
+
+        // Build output is not a change
+        fs::write(dir.path().join("target/debug/demo"), "binary").unwrap();
+        assert!(watcher.scan().is_empty());
+
+        fs::write(dir.path().join("src/lib.rs"), "pub fn demo() { todo!() }\n").unwrap();
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        assert_eq!(
+            watcher.wait_for_change().await,
+            vec![PathBuf::from("src/lib.rs"), PathBuf::from("src/main.rs")]
+        );
+
+        fs::remove_file(dir.path().join("src/main.rs")).unwrap();
+        assert_eq!(watcher.wait_for_change().await, vec![PathBuf::from("src/main.rs")]);
+    }
+
+    #[tokio::test]
+    async fn test_watcher_ignores_reports() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "pub fn demo() {}\n").unwrap();
+        let outputs = vec![dir.path().join("report.json"), PathBuf::from("badges/status.svg")];
+        let mut watcher = Watcher::new(dir.path().to_path_buf(), outputs)
+            .with_timing(Duration::from_millis(10), Duration::from_millis(10));
+
+        // A run writing its report and badges is not a change
+        fs::write(dir.path().join("report.json"), "{}").unwrap();
+        fs::create_dir_all(dir.path().join("badges")).unwrap();
+        fs::write(dir.path().join("badges/status.svg"), "<svg/>").unwrap();
+        assert!(watcher.scan().is_empty());
+        fs::write(dir.path().join("report.json"), "{\"checks\": []}").unwrap();
+        assert!(watcher.scan().is_empty());
+
+        fs::write(dir.path().join("src/lib.rs"), "pub fn demo() { todo!() }\n").unwrap();
+        assert_eq!(watcher.wait_for_change().await, vec![PathBuf::from("src/lib.rs")]);
+    }
+}
@@ -0,0 +1,384 @@
+//! Workspace layout from `cargo metadata`
+//!
+//! `--changed` narrows the compiling checks to the packages a change can
+//! affect: the packages owning the files that differ from a git ref, plus
+//! every workspace member that depends on them, directly or not. The same
+//! layout tags diagnostics and tests with the package they belong to, and
+//! [`Project`] finds the workspace root from anywhere inside it.
+
+use crate::error::{CargoStatusError, Result};
+use crate::tools::CheckResult;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Files at the workspace root that affect every package
+const SHARED_FILES: [&str; 6] = [
+    "Cargo.toml",
+    "Cargo.lock",
+    "rust-toolchain",
+    "rust-toolchain.toml",
+    ".cargo/config",
+    ".cargo/config.toml",
+];
+
+/// Members of a cargo workspace and how they depend on each other
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub root: PathBuf,
+    pub packages: Vec<Package>,
+    /// Cargo's target directory, after `CARGO_TARGET_DIR` and `build.target-dir`
+    pub target_dir: PathBuf,
+}
+
+/// A workspace member
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    pub name: String,
+    /// Directory holding the package's Cargo.toml
+    pub dir: PathBuf,
+    /// Workspace members this package depends on, of any kind
+    pub dependencies: Vec<String>,
+    /// Names of the package's targets (lib, bins, tests, ...)
+    pub targets: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    workspace_root: PathBuf,
+    packages: Vec<MetadataPackage>,
+    target_directory: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct MetadataPackage {
+    name: String,
+    manifest_path: PathBuf,
+    dependencies: Vec<MetadataDependency>,
+    #[serde(default)]
+    targets: Vec<MetadataTarget>,
+}
+
+#[derive(Deserialize)]
+struct MetadataDependency {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct MetadataTarget {
+    name: String,
+}
+
+impl Workspace {
+    /// Reads the workspace containing `dir` with `cargo metadata`
+    pub fn load(dir: &Path) -> Result<Self> {
+        let output = Command::new("cargo")
+            .args(["metadata", "--format-version", "1", "--no-deps"])
+            .current_dir(dir)
+            .output()
+            .map_err(|e| CargoStatusError::command_execution("cargo metadata", e.to_string()))?;
+        if !output.status.success() {
+            return Err(CargoStatusError::command_execution(
+                "cargo metadata",
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Self::from_metadata(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Builds the workspace from `cargo metadata --no-deps` output
+    pub fn from_metadata(json: &str) -> Result<Self> {
+        let metadata: Metadata = serde_json::from_str(json)
+            .map_err(|e| CargoStatusError::other(format!("Invalid cargo metadata: {}", e)))?;
+        let names: BTreeSet<&str> = metadata.packages.iter().map(|package| package.name.as_str()).collect();
+
+        let packages = metadata
+            .packages
+            .iter()
+            .map(|package| Package {
+                name: package.name.clone(),
+                dir: package
+                    .manifest_path
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default(),
+                dependencies: package
+                    .dependencies
+                    .iter()
+                    .filter(|dependency| names.contains(dependency.name.as_str()))
+                    .map(|dependency| dependency.name.clone())
+                    .collect(),
+                targets: package.targets.iter().map(|target| target.name.clone()).collect(),
+            })
+            .collect();
+
+        Ok(Self {
+            root: metadata.workspace_root,
+            packages,
+            target_dir: metadata.target_directory,
+        })
+    }
+
+    /// Packages owning the given absolute paths
+    ///
+    /// A file belongs to the package with the deepest directory containing
+    /// it. Shared files such as the root `Cargo.lock` touch every package;
+    /// other files outside all packages touch none.
+    pub fn packages_for_files(&self, files: &[PathBuf]) -> BTreeSet<String> {
+        let mut affected = BTreeSet::new();
+        for file in files {
+            if SHARED_FILES.iter().any(|shared| *file == self.root.join(shared)) {
+                return self.packages.iter().map(|package| package.name.clone()).collect();
+            }
+            if let Some(package) = self.package_for_file(file) {
+                affected.insert(package.to_string());
+            }
+        }
+        affected
+    }
+
+    /// Package with the deepest directory containing an absolute path
+    pub fn package_for_file(&self, file: &Path) -> Option<&str> {
+        self.packages
+            .iter()
+            .filter(|package| file.starts_with(&package.dir))
+            .max_by_key(|package| package.dir.components().count())
+            .map(|package| package.name.as_str())
+    }
+
+    /// Package owning the target a test binary was built from
+    ///
+    /// Binaries replace `-` with `_` in target names. Returns `None` when no
+    /// member, or more than one, has such a target.
+    pub fn package_for_target(&self, target: &str) -> Option<&str> {
+        let normalize = |name: &str| name.replace('-', "_");
+        let target = normalize(target);
+        let mut owners = self
+            .packages
+            .iter()
+            .filter(|package| package.targets.iter().any(|name| normalize(name) == target));
+        let owner = owners.next()?;
+        owners.next().is_none().then_some(owner.name.as_str())
+    }
+
+    /// Tags diagnostics and test cases that lack a package with their owner
+    ///
+    /// Diagnostics are matched by file, test cases by their suite.
+    pub fn tag_packages(&self, results: &mut [CheckResult]) {
+        for result in results {
+            for diagnostic in &mut result.diagnostics {
+                if diagnostic.crate_name.is_none()
+                    && let Some(ref file) = diagnostic.file
+                {
+                    diagnostic.crate_name = self.package_for_file(&self.root.join(file)).map(String::from);
+                }
+            }
+            for case in &mut result.test_cases {
+                if case.crate_name.is_none()
+                    && let Some(ref suite) = case.suite
+                {
+                    case.crate_name = self.package_for_target(suite).map(String::from);
+                }
+            }
+        }
+    }
+
+    /// The given packages and every member that depends on one of them
+    pub fn with_dependents(&self, packages: BTreeSet<String>) -> BTreeSet<String> {
+        let mut affected = packages;
+        loop {
+            let dependents: Vec<String> = self
+                .packages
+                .iter()
+                .filter(|package| !affected.contains(&package.name))
+                .filter(|package| package.dependencies.iter().any(|dep| affected.contains(dep)))
+                .map(|package| package.name.clone())
+                .collect();
+            if dependents.is_empty() {
+                return affected;
+            }
+            affected.extend(dependents);
+        }
+    }
+}
+
+/// The manifest cargo-status was pointed at and the root of its workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Project {
+    /// `--manifest-path`, or the manifest of the package containing the
+    /// current directory
+    pub manifest_path: PathBuf,
+    /// Directory of the workspace's root `Cargo.toml`
+    pub root: PathBuf,
+}
+
+impl Project {
+    /// Finds the package and workspace for `manifest_path`, or for the
+    /// current directory, with `cargo locate-project`
+    pub fn locate(manifest_path: Option<&Path>) -> Result<Self> {
+        let manifest = locate_project(manifest_path, false)?;
+        let workspace = locate_project(manifest_path, true)?;
+        Ok(Self {
+            manifest_path: manifest,
+            root: workspace.parent().map(Path::to_path_buf).unwrap_or_default(),
+        })
+    }
+
+    /// Whether the manifest is the workspace root's own
+    pub fn is_root(&self) -> bool {
+        self.manifest_path == self.root.join("Cargo.toml")
+    }
+}
+
+/// Name of the package a manifest defines, unless it is a workspace root
+///
+/// Checks pointed at a member cover that package alone; a workspace root
+/// (or a package outside any workspace) covers the whole workspace.
+pub fn member_package(manifest_path: &Path) -> Option<String> {
+    let contents = std::fs::read_to_string(manifest_path).ok()?;
+    let manifest: toml::Table = toml::from_str(&contents).ok()?;
+    if manifest.contains_key("workspace") {
+        return None;
+    }
+    manifest.get("package")?.get("name")?.as_str().map(String::from)
+}
+
+fn locate_project(manifest_path: Option<&Path>, workspace: bool) -> Result<PathBuf> {
+    let mut command = Command::new("cargo");
+    command.args(["locate-project", "--message-format", "plain"]);
+    if workspace {
+        command.arg("--workspace");
+    }
+    if let Some(path) = manifest_path {
+        command.arg("--manifest-path").arg(path);
+    }
+    let output = command
+        .output()
+        .map_err(|e| CargoStatusError::command_execution("cargo locate-project", e.to_string()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.trim().trim_start_matches("error: ");
+        return Err(CargoStatusError::config(format!("No cargo project found: {}", reason)));
+    }
+    Ok(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// Packages affected by changes since `base`, including reverse dependents
+pub fn changed_packages(dir: &Path, base: &str) -> Result<BTreeSet<String>> {
+    let workspace = Workspace::load(dir)?;
+    let files = changed_files(dir, base)?;
+    Ok(workspace.with_dependents(workspace.packages_for_files(&files)))
+}
+
+/// Absolute paths of files that differ from the merge base of `base` and `HEAD`
+///
+/// Includes uncommitted and untracked files, but not ignored ones.
+pub fn changed_files(dir: &Path, base: &str) -> Result<Vec<PathBuf>> {
+    let top_level = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?.trim());
+    let merge_base = git(dir, &["merge-base", base, "HEAD"])
+        .map_err(|_| CargoStatusError::config(format!("Unknown git ref '{}' for --changed", base)))?;
+
+    let changed = git(dir, &["diff", "--name-only", "-z", merge_base.trim()])?;
+    let untracked = git(dir, &["ls-files", "-z", "--others", "--exclude-standard"])?;
+    Ok(changed
+        .split('\0')
+        .chain(untracked.split('\0'))
+        .filter(|path| !path.is_empty())
+        .map(|path| top_level.join(path))
+        .collect())
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let command = format!("git {}", args.join(" "));
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| CargoStatusError::command_execution(&command, e.to_string()))?;
+    if !output.status.success() {
+        return Err(CargoStatusError::command_execution(
+            command,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const METADATA: &str = r#"{
+        "workspace_root": "/ws",
+        "target_directory": "/ws/build",
+        "packages": [
+            {"name": "core", "manifest_path": "/ws/crates/core/Cargo.toml", "dependencies": [{"name": "serde"}]},
+            {"name": "core-macros", "manifest_path": "/ws/crates/core/macros/Cargo.toml", "dependencies": []},
+            {"name": "api", "manifest_path": "/ws/crates/api/Cargo.toml", "dependencies": [{"name": "core"}]},
+            {"name": "cli", "manifest_path": "/ws/crates/cli/Cargo.toml", "dependencies": [{"name": "api"}],
+             "targets": [{"name": "cli"}, {"name": "smoke"}]},
+            {"name": "docs", "manifest_path": "/ws/crates/docs/Cargo.toml", "dependencies": [],
+             "targets": [{"name": "docs-gen"}, {"name": "smoke"}]}
+        ]
+    }"#;
+
+    fn names(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_changed_packages_and_dependents() {
+        let workspace = Workspace::from_metadata(METADATA).unwrap();
+        assert_eq!(workspace.packages[0].dependencies, Vec::<String>::new());
+        assert_eq!(workspace.target_dir, PathBuf::from("/ws/build"));
+
+        let files = vec![
+            PathBuf::from("/ws/crates/core/macros/src/lib.rs"),
+            PathBuf::from("/ws/README.md"),
+        ];
+        assert_eq!(workspace.packages_for_files(&files), names(&["core-macros"]));
+
+        let core = workspace.packages_for_files(&[PathBuf::from("/ws/crates/core/src/lib.rs")]);
+        assert_eq!(workspace.with_dependents(core), names(&["api", "cli", "core"]));
+
+        let lockfile = workspace.packages_for_files(&[PathBuf::from("/ws/Cargo.lock")]);
+        assert_eq!(lockfile.len(), 5);
+    }
+
+    #[test]
+    fn test_locate_project() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("crates/api/src")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"crates/api\"]\n").unwrap();
+        fs::write(
+            root.join("crates/api/Cargo.toml"),
+            "[package]\nname = \"api\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        fs::write(root.join("crates/api/src/lib.rs"), "").unwrap();
+
+        let member = root.join("crates/api/Cargo.toml");
+        let project = Project::locate(Some(&member)).unwrap();
+        assert_eq!(project.manifest_path, member);
+        assert_eq!(project.root, root);
+        assert!(!project.is_root());
+        assert_eq!(member_package(&member).as_deref(), Some("api"));
+        assert_eq!(member_package(&root.join("Cargo.toml")), None);
+
+        let error = Project::locate(Some(&root.join("missing/Cargo.toml"))).unwrap_err();
+        assert!(matches!(error, CargoStatusError::Config(_)));
+    }
+
+    #[test]
+    fn test_package_for_target() {
+        let workspace = Workspace::from_metadata(METADATA).unwrap();
+        assert_eq!(workspace.package_for_target("docs_gen"), Some("docs"));
+        assert_eq!(workspace.package_for_target("cli"), Some("cli"));
+        // Two members have a `smoke` test
+        assert_eq!(workspace.package_for_target("smoke"), None);
+        assert_eq!(workspace.package_for_target("missing"), None);
+    }
+}